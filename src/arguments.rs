@@ -0,0 +1,320 @@
+use std::path::PathBuf;
+
+use colored::Colorize;
+
+use crate::fees::FeeRates;
+use crate::price_cache::format_age;
+use crate::price_history::DEFAULT_HISTORY_WINDOW_SECONDS;
+use crate::price_history::MAXIMUM_HISTORY_WINDOW_SECONDS;
+use crate::price_history::parse_window;
+use crate::price_providers::Provider;
+use crate::prices::PriceCacheSettings;
+use crate::profile_importer::ProfileArguments;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct Arguments {
+    pub(crate) profile: Option<ProfileArguments>,
+    pub(crate) price_provider: Option<Provider>,
+    pub(crate) price_cache: PriceCacheSettings,
+    pub(crate) fee_rates: FeeRates,
+    pub(crate) price_smoothing_window: Option<u64>,
+
+    pub(crate) command: Option<Command>,
+}
+
+// Commands that run instead of the calculator selection.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum Command {
+    // prices refresh
+    RefreshPrices,
+    // prices resell <item IDs>
+    ResellPrices(Vec<String>),
+    // prices bazaar <product IDs>
+    BazaarProducts(Vec<String>),
+    // prices watch [watchlist file]
+    WatchPrices(Option<PathBuf>),
+    // prices history <item ID> [window]
+    PriceHistory(String, u64),
+}
+
+// Parses the arguments given after the binary name, returning None after
+// printing an error if they are invalid:
+// --profile <path or URL>, --member <UUID>,
+// --price-provider <name>, --price-api-url <URL, or path for the file
+// provider>, --price-cache-ttl <seconds>, --offline,
+// --bin-listing-fees <percents under 10M,under 100M,100M or more>,
+// --auction-claim-tax <percent>, --bazaar-tax <percent>,
+// --smooth-prices <window like 30m, 12h or 7d>,
+// prices refresh, prices resell <item IDs>, prices bazaar <product IDs>,
+// prices watch [watchlist file], prices history <item ID> [window], the IDs
+// take the rest of the arguments
+#[inline]
+#[must_use]
+pub(crate) fn parse_arguments(args: &[String]) -> Option<Arguments> {
+    let mut profile = None;
+    let mut member = None;
+    let mut price_provider = None;
+    let mut price_api_url = None;
+    let mut price_cache_ttl = None;
+    let mut bin_listing_fees = None;
+    let mut auction_claim_tax = None;
+    let mut bazaar_tax = None;
+    let mut smooth_prices = None;
+    let mut offline = false;
+    let mut command = None;
+
+    let mut args = args.iter();
+
+    while let Some(argument) = args.next() {
+        let value = match argument.as_str() {
+            "--offline" => {
+                offline = true;
+
+                continue;
+            },
+            "prices" if command.is_none() => {
+                command = Some(parse_prices_command(&mut args)?);
+
+                continue;
+            },
+
+            "--profile" => &mut profile,
+            "--member" => &mut member,
+            "--price-provider" => &mut price_provider,
+            "--price-api-url" => &mut price_api_url,
+            "--price-cache-ttl" => &mut price_cache_ttl,
+            "--bin-listing-fees" => &mut bin_listing_fees,
+            "--auction-claim-tax" => &mut auction_claim_tax,
+            "--bazaar-tax" => &mut bazaar_tax,
+            "--smooth-prices" => &mut smooth_prices,
+
+            _ => {
+                eprintln!("{}{argument}", "invalid argument: ".red());
+
+                return None;
+            },
+        };
+
+        let Some(argument_value) = args.next() else {
+            eprintln!("{}{argument}", "error: missing value for ".red());
+
+            return None;
+        };
+
+        *value = Some(argument_value.clone());
+    }
+
+    let profile = match (profile, member) {
+        (Some(source), member) => Some(ProfileArguments { source, member }),
+        (None, None) => None,
+
+        (None, Some(_)) => {
+            eprintln!("{}", "error: --member needs --profile".red());
+
+            return None;
+        },
+    };
+
+    // Only giving the URL points the default provider to it.
+    let price_provider = match (price_provider, price_api_url) {
+        (None, None) => None,
+
+        (name, url) => {
+            let name = name.as_deref().unwrap_or(Provider::NAMES[0]);
+
+            let Some(provider) = Provider::from_name(name, url.as_deref())
+            else {
+                eprintln!(
+                    "{}{name}, {} {}",
+                    "error: unknown price provider: ".red(),
+                    "valid ones are".red(),
+                    Provider::NAMES.join(", ")
+                );
+
+                return None;
+            };
+
+            Some(provider)
+        },
+    };
+
+    let ttl_seconds = match price_cache_ttl.map(|ttl| ttl.parse::<u64>()) {
+        None => PriceCacheSettings::default().ttl_seconds,
+        Some(Ok(ttl_seconds)) => ttl_seconds,
+
+        Some(Err(e)) => {
+            eprintln!("{}{e}", "error: invalid --price-cache-ttl: ".red());
+
+            return None;
+        },
+    };
+
+    let fee_rates =
+        parse_fee_rates(bin_listing_fees, auction_claim_tax, bazaar_tax)?;
+
+    let price_smoothing_window = match smooth_prices {
+        None => None,
+
+        Some(window) =>
+            Some(parse_window_argument("--smooth-prices", &window)?),
+    };
+
+    Some(Arguments {
+        profile,
+        price_provider,
+        price_cache: PriceCacheSettings { ttl_seconds, offline },
+        fee_rates,
+        price_smoothing_window,
+
+        command,
+    })
+}
+
+#[inline]
+#[must_use]
+fn parse_fee_rates(
+    bin_listing_fees: Option<String>,
+    auction_claim_tax: Option<String>,
+    bazaar_tax: Option<String>,
+) -> Option<FeeRates> {
+    let default_rates = FeeRates::default();
+
+    let bin_listing_fee_percents = match bin_listing_fees {
+        None => default_rates.bin_listing_fee_percents,
+
+        Some(fees) => {
+            let percents = fees
+                .split(',')
+                .map(|fee| parse_percent("--bin-listing-fees", fee))
+                .collect::<Option<Vec<_>>>()?;
+
+            let Ok(percents) = percents.try_into() else {
+                eprintln!(
+                    "{}",
+                    "error: --bin-listing-fees needs 3 comma separated percents"
+                        .red()
+                );
+
+                return None;
+            };
+
+            percents
+        },
+    };
+
+    Some(FeeRates {
+        bin_listing_fee_percents,
+        auction_claim_tax_percent: auction_claim_tax
+            .map_or(Some(default_rates.auction_claim_tax_percent), |tax| {
+                parse_percent("--auction-claim-tax", &tax)
+            })?,
+        bazaar_tax_percent: bazaar_tax
+            .map_or(Some(default_rates.bazaar_tax_percent), |tax| {
+                parse_percent("--bazaar-tax", &tax)
+            })?,
+    })
+}
+
+#[inline]
+#[must_use]
+fn parse_percent(argument: &str, value: &str) -> Option<f64> {
+    match value.trim().parse::<f64>() {
+        Ok(percent) if (0.0..=100.0).contains(&percent) => Some(percent),
+
+        _ => {
+            eprintln!(
+                "{}{value}",
+                format!("error: invalid percent for {argument}: ").red()
+            );
+
+            None
+        },
+    }
+}
+
+#[inline]
+#[must_use]
+fn parse_prices_command<'a>(
+    args: &mut impl Iterator<Item = &'a String>,
+) -> Option<Command> {
+    match args.next().map(String::as_str) {
+        Some("refresh") => Some(Command::RefreshPrices),
+
+        Some("resell") => parse_item_ids(args).map(Command::ResellPrices),
+        Some("bazaar") => parse_item_ids(args).map(Command::BazaarProducts),
+        Some("watch") =>
+            Some(Command::WatchPrices(args.next().map(PathBuf::from))),
+        Some("history") => parse_price_history_command(args),
+
+        Some(command) => {
+            eprintln!("{}{command}", "error: unknown prices command: ".red());
+
+            None
+        },
+
+        None => {
+            eprintln!(
+                "{}",
+                "error: missing prices command, valid ones are: refresh, resell, bazaar, watch, history"
+                    .red()
+            );
+
+            None
+        },
+    }
+}
+
+#[inline]
+#[must_use]
+fn parse_item_ids<'a>(
+    args: impl Iterator<Item = &'a String>,
+) -> Option<Vec<String>> {
+    let ids = args.map(|id| id.to_uppercase()).collect::<Vec<_>>();
+
+    if ids.is_empty() {
+        eprintln!("{}", "error: missing item IDs".red());
+
+        return None;
+    }
+
+    Some(ids)
+}
+
+#[inline]
+#[must_use]
+fn parse_price_history_command<'a>(
+    args: &mut impl Iterator<Item = &'a String>,
+) -> Option<Command> {
+    let Some(id) = args.next() else {
+        eprintln!("{}", "error: missing item ID for prices history".red());
+
+        return None;
+    };
+
+    let window_seconds = match args.next() {
+        None => DEFAULT_HISTORY_WINDOW_SECONDS,
+        Some(window) => parse_window_argument("prices history", window)?,
+    };
+
+    Some(Command::PriceHistory(id.to_uppercase(), window_seconds))
+}
+
+#[inline]
+#[must_use]
+fn parse_window_argument(argument: &str, window: &str) -> Option<u64> {
+    let window_seconds = parse_window(window);
+
+    if window_seconds.is_none() {
+        eprintln!(
+            "{}{window}{}",
+            format!("error: invalid window for {argument}: ").red(),
+            format!(
+                ", use a number followed by m, h or d, up to {}",
+                format_age(MAXIMUM_HISTORY_WINDOW_SECONDS)
+            )
+            .red()
+        );
+    }
+
+    window_seconds
+}
@@ -0,0 +1,219 @@
+use core::cmp;
+
+use std::time::Instant;
+
+use colored::Colorize;
+
+use crate::constants::CATACOMBS_FLOOR_XP;
+use crate::constants::CATACOMBS_XP_PER_LEVEL;
+use crate::constants::CATACOMBS_XP_PER_LEVEL_ABOVE_50;
+use crate::constants::S_PLUS_SCORE;
+use crate::profile_importer::imported_or_ask;
+use crate::utils::ask_float_input;
+use crate::utils::ask_int_input;
+use crate::utils::f64_to_i64;
+use crate::utils::format_coins;
+use crate::utils::i64_to_f64;
+use crate::utils::usize_to_i32;
+
+#[inline]
+pub(crate) fn catacombs_level_planner(
+    start_without_user_input: &mut Option<Instant>,
+) -> bool {
+    let xp = imported_or_ask(
+        "Catacombs XP",
+        |profile| profile.catacombs_xp,
+        // Read as a float, since XP can be above the maximum of integer
+        // inputs.
+        || {
+            f64_to_i64(
+                ask_float_input(
+                    "Enter your current Catacombs XP: ",
+                    Some(0.0),
+                    None,
+                )
+                .trunc(),
+            )
+        },
+    );
+    let current_level = get_catacombs_level(xp);
+
+    println!("You are Catacombs level {current_level}.");
+
+    let target_level = ask_int_input(
+        "Enter your target Catacombs level: ",
+        Some(current_level + 1),
+        Some(i32::MAX),
+    );
+    let score = ask_int_input(
+        &format!("What score do you usually get? ({S_PLUS_SCORE} for S+): "),
+        Some(0),
+        Some(S_PLUS_SCORE),
+    );
+    let xp_boost_percent = ask_float_input(
+        "What's your total Catacombs XP boost in percent? (Catacombs Expert Ring, mayor perks, etc. enter 0 if none): ",
+        Some(0.0),
+        None,
+    );
+    let minutes_per_run = ask_float_input(
+        "How long does a run take you? (in minutes, enter 0 to skip time estimates): ",
+        Some(0.0),
+        None,
+    );
+
+    *start_without_user_input = Some(Instant::now());
+
+    let Some(xp_remaining) = get_catacombs_xp_for_level(target_level)
+        .map(|target_xp| target_xp.saturating_sub(xp))
+    else {
+        eprintln!(
+            "{}{target_level}",
+            "error: XP needed for level is too high to calculate: ".red()
+        );
+
+        return false;
+    };
+
+    println!();
+    println!(
+        "{} XP per run is assumed to scale with score, getting the full XP at S+ ({S_PLUS_SCORE} score). Floor XP values are for the completion only and don't include XP from mobs.",
+        "Note:".red()
+    );
+    println!();
+    println!(
+        "You need {} more XP to reach Catacombs level {target_level}.",
+        format_coins(xp_remaining).bright_yellow()
+    );
+    println!();
+    println!("Runs needed per floor:");
+
+    for (floor, base_xp) in CATACOMBS_FLOOR_XP {
+        let xp_per_run =
+            calculate_xp_per_run(base_xp, score, xp_boost_percent);
+
+        let Some(runs) = calculate_runs_needed(xp_remaining, xp_per_run)
+        else {
+            println!(" {floor}: {}", "(no XP per run)".bright_red());
+
+            continue;
+        };
+
+        if minutes_per_run > 0.0 {
+            println!(
+                " {floor}: {} runs ({} XP per run, {})",
+                format_coins(runs).bright_green(),
+                format_coins(f64_to_i64(xp_per_run.trunc())),
+                format_minutes(i64_to_f64(runs) * minutes_per_run)
+            );
+        } else {
+            println!(
+                " {floor}: {} runs ({} XP per run)",
+                format_coins(runs).bright_green(),
+                format_coins(f64_to_i64(xp_per_run.trunc()))
+            );
+        }
+    }
+
+    true
+}
+
+#[inline]
+#[must_use]
+fn format_minutes(minutes: f64) -> String {
+    let hours = (minutes / 60.0).trunc();
+
+    format!("{hours} hours and {:.0} minutes", minutes % 60.0)
+}
+
+// Total XP needed to reach the level from level 0, None on overflow.
+#[inline]
+#[must_use]
+pub(crate) fn get_catacombs_xp_for_level(level: i32) -> Option<i64> {
+    let level = usize::try_from(level).unwrap_or(0);
+
+    let xp_up_to_50 = CATACOMBS_XP_PER_LEVEL.iter().take(level).sum::<i64>();
+    let levels_above_50 =
+        i64::try_from(level.saturating_sub(CATACOMBS_XP_PER_LEVEL.len()))
+            .ok()?;
+
+    CATACOMBS_XP_PER_LEVEL_ABOVE_50
+        .checked_mul(levels_above_50)?
+        .checked_add(xp_up_to_50)
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn get_catacombs_level(xp: i64) -> i32 {
+    let mut remaining_xp = xp;
+
+    for (level, &xp_for_level) in CATACOMBS_XP_PER_LEVEL.iter().enumerate() {
+        if remaining_xp < xp_for_level {
+            return usize_to_i32(level);
+        }
+
+        remaining_xp -= xp_for_level;
+    }
+
+    let levels_above_50 =
+        cmp::max(0, remaining_xp) / CATACOMBS_XP_PER_LEVEL_ABOVE_50;
+
+    usize_to_i32(CATACOMBS_XP_PER_LEVEL.len())
+        .saturating_add(i32::try_from(levels_above_50).unwrap_or(i32::MAX))
+}
+
+// Level including the progress towards the next level, i.e. 24.5 when half
+// way from level 24 to 25, capped at the maximum level.
+#[inline]
+#[must_use]
+pub(crate) fn get_catacombs_level_with_progress(
+    xp: i64,
+    maximum_level: i32,
+) -> f64 {
+    let level = cmp::min(get_catacombs_level(xp), maximum_level);
+
+    if level >= maximum_level {
+        return f64::from(maximum_level);
+    }
+
+    let (Some(xp_for_level), Some(xp_for_next_level)) = (
+        get_catacombs_xp_for_level(level),
+        get_catacombs_xp_for_level(level + 1),
+    ) else {
+        return f64::from(level);
+    };
+
+    f64::from(level)
+        + i64_to_f64(xp - xp_for_level)
+            / i64_to_f64(xp_for_next_level - xp_for_level)
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn calculate_xp_per_run(
+    base_xp: i64,
+    score: i32,
+    xp_boost_percent: f64,
+) -> f64 {
+    let score_multiplier =
+        f64::from(score.clamp(0, S_PLUS_SCORE)) / f64::from(S_PLUS_SCORE);
+
+    i64_to_f64(base_xp) * score_multiplier * (1.0 + xp_boost_percent / 100.0)
+}
+
+// None if no runs are enough, i.e. when getting no XP per run.
+#[inline]
+#[must_use]
+pub(crate) fn calculate_runs_needed(
+    xp_remaining: i64,
+    xp_per_run: f64,
+) -> Option<i64> {
+    if xp_remaining <= 0 {
+        return Some(0);
+    }
+
+    if xp_per_run <= 0.0 {
+        return None;
+    }
+
+    Some(f64_to_i64((i64_to_f64(xp_remaining) / xp_per_run).ceil()))
+}
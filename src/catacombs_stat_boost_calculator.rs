@@ -2,7 +2,12 @@
 
 use std::time::Instant;
 
+use crate::catacombs_level_planner::catacombs_level_planner;
+use crate::catacombs_level_planner::get_catacombs_level;
 use crate::constants::SECRETS_NEEDED_FOR_MAX_GENERALS_MEDALLION;
+use crate::dungeon_score_calculator::dungeon_score_calculator;
+use crate::profile_importer::imported_or_ask;
+use crate::star_cost_calculator::star_cost_calculator;
 
 use colored::Colorize;
 
@@ -10,73 +15,248 @@
 use crate::utils::percentage_change;
 use crate::utils::u32_to_i32;
 
+// Base stats of dungeon items, in the order they are asked.
+const ITEM_STATS: [&str; 10] = [
+    "Damage",
+    "Strength",
+    "Crit Damage",
+    "Crit Chance",
+    "Health",
+    "Defense",
+    "Intelligence",
+    "Speed",
+    "Attack Speed",
+    "Ferocity",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct DungeonSetup {
+    pub(crate) catacombs_level: i32,
+    pub(crate) normal_stars: i32,
+    pub(crate) master_stars: i32,
+    pub(crate) secrets: i32,
+}
+
+#[inline]
+fn print_modes() {
+    println!();
+    println!("Select what you want to calculate: ");
+    println!(
+        " {}. Stat boost difference between current and planned setups",
+        "1".bright_blue()
+    );
+    println!(" {}. Dungeon item stats", "2".bright_blue());
+    println!(" {}. Catacombs XP and runs to level", "3".bright_blue());
+    println!(" {}. Star and master star upgrade cost", "4".bright_blue());
+    println!(" {}. Run score and S+ predictor", "5".bright_blue());
+}
+
 #[inline]
-pub(crate) fn catacombs_stat_boost_calculator(
+pub(crate) async fn catacombs_stat_boost_calculator(
     start_without_user_input: &mut Option<Instant>,
 ) -> bool {
-    let catacombs_boost = get_cata_stat_boost(ask_int_input(
-        "Enter your current Catacombs level: ",
-        Some(0),
-        Some(i32::MAX),
-    ));
-
-    let normal_stars_boost = 10
-        * ask_int_input(
-            "Enter the amount of normal stars your gear has: ",
-            Some(0),
-            Some(5),
-        );
-
-    let master_stars_boost = 5 * ask_int_input(
-        "Enter the amount of master stars your gear has: ",
-        Some(0),
-        Some(5),
-    );
+    print_modes();
 
-    let generals_medallion_stat_boost = u32_to_i32(cmp::min(SECRETS_NEEDED_FOR_MAX_GENERALS_MEDALLION, ask_int_input("Enter the amount of secrets you have (Enter 0 if you don't have General's Medallion): ", Some(0), Some(i32::MAX))).checked_ilog10().unwrap_or(0)) + 1;
+    let mode = ask_int_input("Enter a number to select: ", Some(1), Some(5));
 
-    let planned_catacombs_level_boost = get_cata_stat_boost(ask_int_input(
-        "Enter your planned Catacombs Level: ",
-        Some(0),
-        Some(i32::MAX),
-    ));
+    match mode {
+        1 => stat_boost_difference_calculator(start_without_user_input),
+        2 => item_stat_calculator(start_without_user_input),
+        3 => catacombs_level_planner(start_without_user_input),
+        4 => star_cost_calculator(start_without_user_input).await,
+        5 => dungeon_score_calculator(start_without_user_input),
 
-    let planned_normal_stars_boost = 10
-        * ask_int_input(
-            "Enter the amount of normal stars you plan your gear to have: ",
-            Some(0),
-            Some(5),
-        );
+        _ => {
+            eprintln!("{}{mode}", "error: invalid selection: ".red());
 
-    let planned_master_stars_boost = 5 * ask_int_input(
-        "Enter the amount of master stars you plan your gear to have: ",
-        Some(0),
-        Some(5),
-    );
+            false
+        },
+    }
+}
 
-    let planned_generals_medallion_stat_boost = u32_to_i32(cmp::min(SECRETS_NEEDED_FOR_MAX_GENERALS_MEDALLION, ask_int_input("Enter the amount of secrets you plan to have (Enter 0 if you don't plan to have General's Medallion): ", Some(0), Some(i32::MAX))).checked_ilog10().unwrap_or(0)) + 1;
+#[inline]
+fn stat_boost_difference_calculator(
+    start_without_user_input: &mut Option<Instant>,
+) -> bool {
+    let total_stat_boost =
+        get_total_dungeon_stat_boost(ask_dungeon_setup(false));
+    let planned_total_stat_boost =
+        get_total_dungeon_stat_boost(ask_dungeon_setup(true));
 
     *start_without_user_input = Some(Instant::now());
 
-    let total_stat_boost = catacombs_boost
-        + normal_stars_boost
-        + master_stars_boost
-        + generals_medallion_stat_boost;
+    println!();
+    println!("{}{}{}", "Difference between your current and planned Catacombs level, Stars/Master Stars and General's Medallion boost in percent is %".bright_green(), percentage_change(From::from(total_stat_boost), From::from(planned_total_stat_boost)).to_string().bright_yellow(), ".".white());
+
+    true
+}
+
+#[inline]
+fn item_stat_calculator(
+    start_without_user_input: &mut Option<Instant>,
+) -> bool {
+    let base_stats = ITEM_STATS
+        .iter()
+        .map(|&stat| {
+            (
+                stat,
+                ask_int_input(
+                    &format!(
+                        "Enter the item's base {stat} (Enter 0 if it has none): "
+                    ),
+                    Some(i32::MIN),
+                    Some(i32::MAX),
+                ),
+            )
+        })
+        .filter(|&(_, value)| value != 0)
+        .collect::<Vec<_>>();
+
+    let stat_boost = get_total_dungeon_stat_boost(ask_dungeon_setup(false));
+    let planned_stat_boost =
+        get_total_dungeon_stat_boost(ask_dungeon_setup(true));
 
-    let planned_total_stat_boost = planned_catacombs_level_boost
-        + planned_normal_stars_boost
-        + planned_master_stars_boost
-        + planned_generals_medallion_stat_boost;
+    *start_without_user_input = Some(Instant::now());
 
     println!();
-    println!("{}{}{}", "Difference between your current and planned Catacombs level, Stars/Master Stars and General's Medallion boost in percent is %".bright_green(), percentage_change(From::from(total_stat_boost), From::from(planned_total_stat_boost)).to_string().bright_yellow(), ".".white());
+
+    if base_stats.is_empty() {
+        println!("{}", "The item has no stats to boost.".bright_red());
+
+        return true;
+    }
+
+    println!(
+        "Stats in Dungeons with your current ({}) and planned ({}) stat boosts:",
+        format!("+{stat_boost}%").bright_green(),
+        format!("+{planned_stat_boost}%").bright_green()
+    );
+
+    for (stat, base_value) in base_stats {
+        println!(
+            " {stat}: {base_value} -> {} -> {}",
+            format_stat(calculate_dungeon_stat(base_value, stat_boost))
+                .bright_yellow(),
+            format_stat(calculate_dungeon_stat(
+                base_value,
+                planned_stat_boost
+            ))
+            .bright_yellow()
+        );
+    }
 
     true
 }
 
 #[inline]
 #[must_use]
-fn get_cata_stat_boost(catacombs_level: i32) -> i32 {
+fn format_stat(value: f64) -> String {
+    // Stats are shown with at most 1 decimal in-game.
+    let rounded = (value * 10.0).round() / 10.0;
+
+    rounded.to_string()
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn ask_dungeon_setup(planned: bool) -> DungeonSetup {
+    if planned {
+        DungeonSetup {
+            catacombs_level: ask_int_input(
+                "Enter your planned Catacombs Level: ",
+                Some(0),
+                Some(i32::MAX),
+            ),
+            normal_stars: ask_int_input(
+                "Enter the amount of normal stars you plan your gear to have: ",
+                Some(0),
+                Some(5),
+            ),
+            master_stars: ask_int_input(
+                "Enter the amount of master stars you plan your gear to have: ",
+                Some(0),
+                Some(5),
+            ),
+            secrets: ask_int_input(
+                "Enter the amount of secrets you plan to have (Enter 0 if you don't plan to have General's Medallion): ",
+                Some(0),
+                Some(i32::MAX),
+            ),
+        }
+    } else {
+        DungeonSetup {
+            catacombs_level: imported_or_ask(
+                "Catacombs level",
+                |profile| profile.catacombs_xp.map(get_catacombs_level),
+                || {
+                    ask_int_input(
+                        "Enter your current Catacombs level: ",
+                        Some(0),
+                        Some(i32::MAX),
+                    )
+                },
+            ),
+            normal_stars: ask_int_input(
+                "Enter the amount of normal stars your gear has: ",
+                Some(0),
+                Some(5),
+            ),
+            master_stars: ask_int_input(
+                "Enter the amount of master stars your gear has: ",
+                Some(0),
+                Some(5),
+            ),
+            secrets: imported_or_ask(
+                "secrets",
+                |profile| profile.secrets,
+                || {
+                    ask_int_input(
+                        "Enter the amount of secrets you have (Enter 0 if you don't have General's Medallion): ",
+                        Some(0),
+                        Some(i32::MAX),
+                    )
+                },
+            ),
+        }
+    }
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn get_total_dungeon_stat_boost(setup: DungeonSetup) -> i32 {
+    get_cata_stat_boost(setup.catacombs_level)
+        + get_stars_stat_boost(setup.normal_stars, setup.master_stars)
+        + get_generals_medallion_stat_boost(setup.secrets)
+}
+
+#[inline]
+#[must_use]
+pub(crate) const fn get_stars_stat_boost(
+    normal_stars: i32,
+    master_stars: i32,
+) -> i32 {
+    10 * normal_stars + 5 * master_stars
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn get_generals_medallion_stat_boost(secrets: i32) -> i32 {
+    u32_to_i32(
+        cmp::min(SECRETS_NEEDED_FOR_MAX_GENERALS_MEDALLION, secrets)
+            .checked_ilog10()
+            .unwrap_or(0),
+    ) + 1
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn calculate_dungeon_stat(base_value: i32, stat_boost: i32) -> f64 {
+    f64::from(base_value) * (1.0 + f64::from(stat_boost) / 100.0)
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn get_cata_stat_boost(catacombs_level: i32) -> i32 {
     let mut cata_stat_boost = 0;
 
     for level in 0..=catacombs_level {
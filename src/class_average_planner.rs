@@ -0,0 +1,324 @@
+use std::time::Instant;
+
+use colored::Colorize;
+
+use crate::catacombs_level_planner::calculate_xp_per_run;
+use crate::catacombs_level_planner::get_catacombs_level;
+use crate::catacombs_level_planner::get_catacombs_level_with_progress;
+use crate::catacombs_level_planner::get_catacombs_xp_for_level;
+use crate::constants::CATACOMBS_FLOOR_XP;
+use crate::constants::S_PLUS_SCORE;
+use crate::party_buffs::ALL_DUNGEON_CLASSES;
+use crate::profile_importer::imported_profile;
+use crate::profile_importer::print_imported_value;
+use crate::utils::ask_float_input;
+use crate::utils::ask_int_input;
+use crate::utils::f64_to_i64;
+use crate::utils::format_coins;
+use crate::utils::i64_to_f64;
+use crate::utils::usize_to_f64;
+use crate::utils::usize_to_i32;
+
+// Class levels use the same XP table as Catacombs, but the class average only
+// counts up to level 50.
+const MAXIMUM_CLASS_LEVEL: i32 = 50;
+
+// Source: https://wiki.hypixel.net/Dungeon_Classes#Leveling
+// The class you play gets the full XP of a run, the others get a share of it.
+const OFF_CLASS_XP_SHARE_PERCENT: f64 = 25.0;
+
+// Stop planning after this many runs, i.e. when the target is too far away.
+const MAXIMUM_PLANNED_RUNS: i64 = 1_000_000;
+
+const CLASS_COUNT: usize = ALL_DUNGEON_CLASSES.len();
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ClassPlan {
+    pub(crate) runs_per_class: [i64; CLASS_COUNT],
+    pub(crate) xp: [f64; CLASS_COUNT],
+}
+
+impl ClassPlan {
+    #[inline]
+    #[must_use]
+    pub(crate) fn total_runs(&self) -> i64 {
+        self.runs_per_class.iter().sum()
+    }
+}
+
+#[inline]
+pub(crate) fn class_average_planner(
+    start_without_user_input: &mut Option<Instant>,
+) -> bool {
+    let xp = imported_profile()
+        .and_then(|profile| profile.class_xp)
+        .map_or_else(ask_class_xp, |class_xp| {
+            for (class, &value) in ALL_DUNGEON_CLASSES.iter().zip(&class_xp) {
+                print_imported_value(&format!("{class} XP"), value.trunc());
+            }
+
+            class_xp
+        });
+
+    let class_average = calculate_class_average(&xp);
+
+    println!(
+        "Your Class Average is {class_average:.2}. Max Class Average is {MAXIMUM_CLASS_LEVEL}."
+    );
+
+    let target_class_average = ask_float_input(
+        "What is your target Class Average?: ",
+        Some(0.0),
+        Some(f64::from(MAXIMUM_CLASS_LEVEL)),
+    );
+
+    println!();
+    println!("Select the floor you will run: ");
+
+    for (index, (floor, _)) in CATACOMBS_FLOOR_XP.iter().enumerate() {
+        println!(" {}. {floor}", (index + 1).to_string().bright_blue());
+    }
+
+    let floor_selection = ask_int_input(
+        "Enter a number to select: ",
+        Some(1),
+        Some(usize_to_i32(CATACOMBS_FLOOR_XP.len())),
+    );
+    let Some(&(floor, base_xp)) = usize::try_from(floor_selection - 1)
+        .ok()
+        .and_then(|index| CATACOMBS_FLOOR_XP.get(index))
+    else {
+        eprintln!("{}{floor_selection}", "error: invalid selection: ".red());
+
+        return false;
+    };
+
+    let score = ask_int_input(
+        &format!("What score do you usually get? ({S_PLUS_SCORE} for S+): "),
+        Some(0),
+        Some(S_PLUS_SCORE),
+    );
+    let xp_boost_percent = ask_float_input(
+        "What's your total class XP boost in percent? (enter 0 if none): ",
+        Some(0.0),
+        None,
+    );
+
+    *start_without_user_input = Some(Instant::now());
+
+    let xp_per_run = calculate_xp_per_run(base_xp, score, xp_boost_percent);
+
+    let Some(plan) = plan_class_runs(xp, xp_per_run, target_class_average)
+    else {
+        println!();
+        println!(
+            "{}",
+            "Can't reach your target Class Average with these runs."
+                .bright_red()
+        );
+
+        return true;
+    };
+
+    println!();
+    println!(
+        "{} The class you play gets the full XP of a run, other classes get {OFF_CLASS_XP_SHARE_PERCENT}% of it. Floor XP values are for the completion only and don't include XP from mobs.",
+        "Note:".red()
+    );
+    println!();
+    println!(
+        "You need {} {floor} runs in total to reach your target Class Average, playing these classes:",
+        format_coins(plan.total_runs()).bright_green()
+    );
+
+    for (index, class) in ALL_DUNGEON_CLASSES.iter().enumerate() {
+        let (Some(&runs), Some(&class_xp)) =
+            (plan.runs_per_class.get(index), plan.xp.get(index))
+        else {
+            continue;
+        };
+
+        println!(
+            " {class}: {} runs, reaching level {:.2}",
+            format_coins(runs),
+            get_class_level(class_xp)
+        );
+    }
+
+    println!();
+    println!(
+        "Your Class Average will be {:.2}.",
+        calculate_class_average(&plan.xp)
+    );
+
+    true
+}
+
+#[inline]
+#[must_use]
+fn ask_class_xp() -> [f64; CLASS_COUNT] {
+    println!();
+    println!(" {}. Enter class levels", "1".bright_blue());
+    println!(" {}. Enter class XP", "2".bright_blue());
+
+    let enters_levels =
+        ask_int_input("Enter a number to select: ", Some(1), Some(2)) == 1;
+
+    let mut xp = [0.0; CLASS_COUNT];
+
+    for (class, class_xp) in ALL_DUNGEON_CLASSES.iter().zip(&mut xp) {
+        let value = if enters_levels {
+            get_catacombs_xp_for_level(ask_int_input(
+                &format!("What is your {class} level?: "),
+                Some(0),
+                Some(MAXIMUM_CLASS_LEVEL),
+            ))
+            .unwrap_or(0)
+        } else {
+            f64_to_i64(
+                ask_float_input(
+                    &format!("What is your {class} XP?: "),
+                    Some(0.0),
+                    None,
+                )
+                .trunc(),
+            )
+        };
+
+        *class_xp = i64_to_f64(value);
+    }
+
+    xp
+}
+
+#[inline]
+#[must_use]
+fn get_class_level(xp: f64) -> f64 {
+    get_catacombs_level_with_progress(
+        f64_to_i64(xp.trunc()),
+        MAXIMUM_CLASS_LEVEL,
+    )
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn calculate_class_average(xp: &[f64]) -> f64 {
+    if xp.is_empty() {
+        return 0.0;
+    }
+
+    xp.iter().map(|&class_xp| get_class_level(class_xp)).sum::<f64>()
+        / usize_to_f64(xp.len())
+}
+
+// Picks the class to play for each run greedily, the one that raises the class
+// average the most. None if the target can't be reached.
+#[inline]
+#[must_use]
+pub(crate) fn plan_class_runs(
+    xp: [f64; CLASS_COUNT],
+    xp_per_run: f64,
+    target_class_average: f64,
+) -> Option<ClassPlan> {
+    let mut plan = ClassPlan { runs_per_class: [0; CLASS_COUNT], xp };
+    let off_class_xp = xp_per_run * OFF_CLASS_XP_SHARE_PERCENT / 100.0;
+
+    let class_xp_per_run = |played: usize, index: usize| {
+        if index == played { xp_per_run } else { off_class_xp }
+    };
+
+    loop {
+        let class_average = calculate_class_average(&plan.xp);
+
+        if class_average >= target_class_average {
+            return Some(plan);
+        }
+
+        let total_runs = plan.total_runs();
+
+        if xp_per_run <= 0.0 || total_runs >= MAXIMUM_PLANNED_RUNS {
+            return None;
+        }
+
+        let xp_after_runs = |played: usize, runs: i64| {
+            let mut xp = plan.xp;
+
+            for (index, class_xp) in xp.iter_mut().enumerate() {
+                *class_xp = class_xp_per_run(played, index)
+                    .mul_add(i64_to_f64(runs), *class_xp);
+            }
+
+            xp
+        };
+
+        let (best, best_class_average) = (0..CLASS_COUNT)
+            .map(|played| {
+                (played, calculate_class_average(&xp_after_runs(played, 1)))
+            })
+            .max_by(|(_, average), (_, other)| average.total_cmp(other))?;
+
+        let gain_per_run = best_class_average - class_average;
+
+        // Every class is at the maximum level.
+        if gain_per_run <= 0.0 {
+            return None;
+        }
+
+        // Levels go up linearly with XP, so the class average does too until
+        // one of the classes levels up, playing the same class until then
+        // instead of deciding one run at a time.
+        let runs_until_level_up = plan
+            .xp
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &class_xp)| {
+                get_runs_to_next_class_level(
+                    class_xp,
+                    class_xp_per_run(best, index),
+                )
+            })
+            .min()
+            .unwrap_or(1);
+        let runs_until_target = get_runs_for_xp(
+            target_class_average - class_average,
+            gain_per_run,
+        );
+
+        let runs = runs_until_level_up
+            .min(runs_until_target)
+            .clamp(1, MAXIMUM_PLANNED_RUNS - total_runs);
+
+        plan.xp = xp_after_runs(best, runs);
+
+        if let Some(class_runs) = plan.runs_per_class.get_mut(best) {
+            *class_runs += runs;
+        }
+    }
+}
+
+// None if the class is already at the maximum level.
+#[inline]
+#[must_use]
+fn get_runs_to_next_class_level(xp: f64, xp_per_run: f64) -> Option<i64> {
+    let level = get_catacombs_level(f64_to_i64(xp.trunc()));
+
+    if level >= MAXIMUM_CLASS_LEVEL {
+        return None;
+    }
+
+    let xp_for_next_level = get_catacombs_xp_for_level(level + 1)?;
+
+    Some(get_runs_for_xp(i64_to_f64(xp_for_next_level) - xp, xp_per_run))
+}
+
+// Runs needed to get the amount with the given amount per run, at least 1 and
+// at most the maximum planned runs.
+#[inline]
+#[must_use]
+fn get_runs_for_xp(amount: f64, amount_per_run: f64) -> i64 {
+    f64_to_i64(
+        (amount / amount_per_run)
+            .ceil()
+            .clamp(1.0, i64_to_f64(MAXIMUM_PLANNED_RUNS)),
+    )
+}
@@ -25,59 +25,173 @@
 // Source: In-game RNG Meter base chance.
 pub(crate) const DARK_CLAYMORE_DROP_CHANCE: f64 = 0.0705;
 
-// Shadow Assassin
-// Source: https://wiki.hypixel.net/Shadow_Assassin#Stats
-pub(crate) const F3_SHADOW_ASSASSIN_DAMAGE: i32 = 3280;
-pub(crate) const F4_SHADOW_ASSASSIN_DAMAGE: i32 = F3_SHADOW_ASSASSIN_DAMAGE;
-pub(crate) const F5_SHADOW_ASSASSIN_DAMAGE: i32 = 6640; // Has 2 variants, take the higher one
-pub(crate) const F6_SHADOW_ASSASSIN_DAMAGE: i32 = 8640;
-pub(crate) const F7_SHADOW_ASSASSIN_DAMAGE: i32 = 48000; // Has 2 variants, take the higher one
-
-// Master Shadow Assassin
-// Source: https://wiki.hypixel.net/Shadow_Assassin#Master_Mode_Shadow_Assassin
-pub(crate) const M3_SHADOW_ASSASSIN_DAMAGE: i32 = 175_000;
-pub(crate) const M4_SHADOW_ASSASSIN_DAMAGE: i32 = M3_SHADOW_ASSASSIN_DAMAGE;
-pub(crate) const M5_SHADOW_ASSASSIN_DAMAGE: i32 = 270_000; // Has 2 variants, take the higher one
-pub(crate) const M6_SHADOW_ASSASSIN_DAMAGE: i32 = 400_000;
-pub(crate) const M7_SHADOW_ASSASSIN_DAMAGE: i32 = M6_SHADOW_ASSASSIN_DAMAGE; // Has 2 variants, but they do the same damage unlike F7, and they also do same
-// damage as Shadow Assassins on M6. (although they have much more HP)
-
-// Fels
-// Source: https://wiki.hypixel.net/Fels#Stats
-pub(crate) const F5_FELS_DAMAGE: i32 = 8000;
-pub(crate) const F6_FELS_DAMAGE: i32 = 9600;
-pub(crate) const F7_FELS_DAMAGE: i32 = 20000;
-
-// Master Fels
-// Source: https://wiki.hypixel.net/Fel#Master_Mode_Fels
-pub(crate) const M5_FELS_DAMAGE: i32 = 150_000;
-pub(crate) const M6_FELS_DAMAGE: i32 = 200_000;
-pub(crate) const M7_FELS_DAMAGE: i32 = 240_000;
-
-// Voidgloom Seraph (Tier 1)
-pub(crate) const VOIDGLOOM_SERAPH_TIER_1_BASE_DAMAGE: i32 = 1200;
-pub(crate) const VOIDGLOOM_SERAPH_TIER_1_AOE_DAMAGE: i32 = 720;
-
-pub(crate) const VOIDGLOOM_SERAPH_TIER_1_TOTAL_DAMAGE: i32 =
-    VOIDGLOOM_SERAPH_TIER_1_BASE_DAMAGE + VOIDGLOOM_SERAPH_TIER_1_AOE_DAMAGE;
-
-// Voidgloom Seraph (Tier 2)
-pub(crate) const VOIDGLOOM_SERAPH_TIER_2_BASE_DAMAGE: i32 = 5000;
-pub(crate) const VOIDGLOOM_SERAPH_TIER_2_AOE_DAMAGE: i32 = 3000;
-
-pub(crate) const VOIDGLOOM_SERAPH_TIER_2_TOTAL_DAMAGE: i32 =
-    VOIDGLOOM_SERAPH_TIER_2_BASE_DAMAGE + VOIDGLOOM_SERAPH_TIER_2_AOE_DAMAGE;
-
-// Voidgloom Seraph (Tier 3)
-pub(crate) const VOIDGLOOM_SERAPH_TIER_3_BASE_DAMAGE: i32 = 12000;
-pub(crate) const VOIDGLOOM_SERAPH_TIER_3_AOE_DAMAGE: i32 = 7200;
-
-pub(crate) const VOIDGLOOM_SERAPH_TIER_3_TOTAL_DAMAGE: i32 =
-    VOIDGLOOM_SERAPH_TIER_3_BASE_DAMAGE + VOIDGLOOM_SERAPH_TIER_3_AOE_DAMAGE;
-
-// Voidgloom Seraph (Tier 4)
-pub(crate) const VOIDGLOOM_SERAPH_TIER_4_BASE_DAMAGE: i32 = 21000;
-pub(crate) const VOIDGLOOM_SERAPH_TIER_4_AOE_DAMAGE: i32 = 12600;
-
-pub(crate) const VOIDGLOOM_SERAPH_TIER_4_TOTAL_DAMAGE: i32 =
-    VOIDGLOOM_SERAPH_TIER_4_BASE_DAMAGE + VOIDGLOOM_SERAPH_TIER_4_AOE_DAMAGE;
+// Source: https://wiki.hypixel.net/Catacombs#Leveling
+// XP needed to reach each level from the previous one, up to level 50.
+pub(crate) const CATACOMBS_XP_PER_LEVEL: [i64; 50] = [
+    50,
+    75,
+    110,
+    160,
+    230,
+    330,
+    470,
+    670,
+    950,
+    1_340,
+    1_890,
+    2_665,
+    3_760,
+    5_260,
+    7_380,
+    10_300,
+    14_400,
+    20_000,
+    27_600,
+    38_000,
+    52_500,
+    71_500,
+    97_000,
+    132_000,
+    180_000,
+    243_000,
+    328_000,
+    445_000,
+    600_000,
+    800_000,
+    1_065_000,
+    1_410_000,
+    1_900_000,
+    2_500_000,
+    3_300_000,
+    4_300_000,
+    5_600_000,
+    7_200_000,
+    9_200_000,
+    12_000_000,
+    15_000_000,
+    19_000_000,
+    24_000_000,
+    30_000_000,
+    38_000_000,
+    48_000_000,
+    60_000_000,
+    75_000_000,
+    93_000_000,
+    116_250_000,
+];
+
+// Source: https://wiki.hypixel.net/Catacombs#Leveling
+// Levels above 50 don't give any more stat boost, but still need XP.
+pub(crate) const CATACOMBS_XP_PER_LEVEL_ABOVE_50: i64 = 200_000_000;
+
+// Source: https://wiki.hypixel.net/Catacombs#Experience
+// Catacombs XP for completing each floor with S+ score, without any boosts.
+pub(crate) const CATACOMBS_FLOOR_XP: [(&str, i64); 15] = [
+    ("Entrance", 50),
+    ("F1", 80),
+    ("F2", 160),
+    ("F3", 400),
+    ("F4", 1_420),
+    ("F5", 2_400),
+    ("F6", 5_000),
+    ("F7", 28_000),
+    ("M1", 10_000),
+    ("M2", 15_000),
+    ("M3", 36_500),
+    ("M4", 48_000),
+    ("M5", 70_000),
+    ("M6", 100_000),
+    ("M7", 300_000),
+];
+
+// Source: https://wiki.hypixel.net/Skills#Leveling
+// XP needed to reach each level from the previous one, the same for every
+// Skill in the Skill Average. Skills capped at 50 only use the first 50.
+pub(crate) const SKILL_XP_PER_LEVEL: [i64; 60] = [
+    50,
+    125,
+    200,
+    300,
+    500,
+    750,
+    1_000,
+    1_500,
+    2_000,
+    3_500,
+    5_000,
+    7_500,
+    10_000,
+    15_000,
+    20_000,
+    30_000,
+    50_000,
+    75_000,
+    100_000,
+    200_000,
+    300_000,
+    400_000,
+    500_000,
+    600_000,
+    700_000,
+    800_000,
+    900_000,
+    1_000_000,
+    1_100_000,
+    1_200_000,
+    1_300_000,
+    1_400_000,
+    1_500_000,
+    1_600_000,
+    1_700_000,
+    1_800_000,
+    1_900_000,
+    2_000_000,
+    2_100_000,
+    2_200_000,
+    2_300_000,
+    2_400_000,
+    2_500_000,
+    2_600_000,
+    2_750_000,
+    2_900_000,
+    3_100_000,
+    3_400_000,
+    3_700_000,
+    4_000_000,
+    4_300_000,
+    4_600_000,
+    4_900_000,
+    5_200_000,
+    5_500_000,
+    5_800_000,
+    6_100_000,
+    6_400_000,
+    6_700_000,
+    7_000_000,
+];
+
+// Score needed for S+, which gives the full XP of a floor.
+pub(crate) const S_PLUS_SCORE: i32 = 300;
+
+// Source: https://wiki.hypixel.net/Dungeon_Score
+// Percent of the secrets that need to be found for full Exploration score,
+// the time limit in seconds for full Speed score and whether there is a Mimic
+// on each floor. These are approximations, update them if the values on the
+// wiki change.
+pub(crate) const DUNGEON_FLOOR_REQUIREMENTS: [(&str, i32, i32, bool); 15] = [
+    ("Entrance", 30, 600, false),
+    ("F1", 30, 600, false),
+    ("F2", 40, 600, false),
+    ("F3", 50, 600, false),
+    ("F4", 60, 600, false),
+    ("F5", 70, 600, false),
+    ("F6", 85, 720, true),
+    ("F7", 100, 840, true),
+    ("M1", 100, 480, false),
+    ("M2", 100, 480, false),
+    ("M3", 100, 480, false),
+    ("M4", 100, 480, false),
+    ("M5", 100, 480, false),
+    ("M6", 100, 600, true),
+    ("M7", 100, 840, true),
+];
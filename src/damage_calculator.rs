@@ -0,0 +1,342 @@
+use std::time::Instant;
+
+use colored::Colorize;
+
+use crate::catacombs_stat_boost_calculator::ask_dungeon_setup;
+use crate::catacombs_stat_boost_calculator::get_total_dungeon_stat_boost;
+use crate::enemy_database::select_enemy_health;
+use crate::utils::ask_float_input;
+use crate::utils::ask_int_input;
+use crate::utils::f64_to_i64;
+use crate::utils::i64_to_f64;
+use crate::utils::with_comma_separators;
+
+// Every weapon deals 5 more damage than its Damage stat.
+const BASE_WEAPON_DAMAGE: i32 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct DamageParameters {
+    pub(crate) weapon_damage: i32,
+    pub(crate) strength: i32,
+    pub(crate) crit_damage: i32,
+    pub(crate) crit_chance: i32,
+
+    // Additive multipliers in percent, i.e. Sharpness, Giant Killer and
+    // Combat level.
+    pub(crate) additive_multiplier_percent: f64,
+    // Multiplicative multipliers, i.e. armor set bonuses. 1.0 for none.
+    pub(crate) multiplicative_multiplier: f64,
+
+    // Boosts Damage, Strength and Crit Damage of dungeon items in percent. 0
+    // outside Dungeons.
+    pub(crate) dungeon_stat_boost_percent: i32,
+    // The boost only applies to the stats of dungeon items, so these are the
+    // parts of the Strength and Crit Damage above coming from them.
+    pub(crate) dungeon_item_strength: i32,
+    pub(crate) dungeon_item_crit_damage: i32,
+    pub(crate) dungeon_weapon: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct DamageResult {
+    pub(crate) non_crit: f64,
+    pub(crate) crit: f64,
+
+    // Average damage per hit considering Crit Chance.
+    pub(crate) expected: f64,
+}
+
+#[inline]
+pub(crate) fn damage_calculator(
+    start_without_user_input: &mut Option<Instant>,
+) -> bool {
+    let Some(health) = select_enemy_health() else {
+        return false;
+    };
+
+    println!();
+    println!(
+        "{} Enter your stats as shown outside of Dungeons, the Dungeon stat boost is applied separately to the stats of dungeon items.",
+        "Note:".red()
+    );
+
+    let weapon_damage =
+        ask_int_input("What's your weapon's Damage?: ", Some(0), None);
+    let strength = ask_int_input("What's your Strength?: ", Some(0), None);
+    let crit_damage =
+        ask_int_input("What's your Crit Damage?: ", Some(0), None);
+    let crit_chance =
+        ask_int_input("What's your Crit Chance?: ", Some(0), None);
+
+    let additive_multiplier_percent = ask_float_input(
+        "What's your total additive damage bonus in percent (enchantments like Sharpness, Combat level, etc.)?: ",
+        Some(0.0),
+        None,
+    );
+    let multiplicative_multiplier = ask_float_input(
+        "What's your multiplicative damage multiplier (armor set bonuses, abilities, etc., enter 1 for none)?: ",
+        Some(0.0),
+        None,
+    );
+
+    println!();
+    println!(" {}. In Dungeons", "1".bright_blue());
+    println!(" {}. Outside of Dungeons", "2".bright_blue());
+
+    let location =
+        ask_int_input("Enter a number to select: ", Some(1), Some(2));
+
+    let mut parameters = DamageParameters {
+        weapon_damage,
+        strength,
+        crit_damage,
+        crit_chance,
+
+        additive_multiplier_percent,
+        multiplicative_multiplier,
+
+        dungeon_stat_boost_percent: 0,
+        dungeon_item_strength: 0,
+        dungeon_item_crit_damage: 0,
+        dungeon_weapon: false,
+    };
+
+    if location == 1 {
+        ask_dungeon_stat_boost(&mut parameters);
+    }
+
+    *start_without_user_input = Some(Instant::now());
+
+    let damage = calculate_damage(parameters);
+
+    println!();
+    println!(
+        "You deal {} damage per hit, {} with a critical hit, and {} on average considering your Crit Chance.",
+        format_damage(damage.non_crit),
+        format_damage(damage.crit).bright_green(),
+        format_damage(damage.expected)
+    );
+
+    println!(
+        "Your enemy has {} Health, taking {} hits to kill with critical hits or {} hits on average.",
+        with_comma_separators(&health.to_string())
+            .unwrap_or_else(|| health.to_string()),
+        hits_to_kill_to_string(calculate_hits_to_kill(health, damage.crit)),
+        hits_to_kill_to_string(calculate_hits_to_kill(
+            health,
+            damage.expected
+        ))
+    );
+
+    if damage.crit >= i64_to_f64(health) {
+        println!();
+        println!(
+            "{}",
+            "You can already kill your enemy in one critical hit."
+                .bright_green()
+        );
+
+        return true;
+    }
+
+    print_needed_stats_to_one_shot(parameters, health);
+
+    if crit_chance < 100 {
+        println!(
+            "{} Your Crit Chance is below 100, so not every hit will be a critical hit.",
+            "Note:".red()
+        );
+    }
+
+    true
+}
+
+#[inline]
+fn ask_dungeon_stat_boost(parameters: &mut DamageParameters) {
+    parameters.dungeon_stat_boost_percent =
+        get_total_dungeon_stat_boost(ask_dungeon_setup(false));
+    parameters.dungeon_item_strength = ask_int_input(
+        "How much of your Strength comes from dungeon items?: ",
+        Some(0),
+        Some(parameters.strength),
+    );
+    parameters.dungeon_item_crit_damage = ask_int_input(
+        "How much of your Crit Damage comes from dungeon items?: ",
+        Some(0),
+        Some(parameters.crit_damage),
+    );
+    parameters.dungeon_weapon = ask_int_input(
+        "Is your weapon a dungeon item? (1 for yes, 2 for no): ",
+        Some(1),
+        Some(2),
+    ) == 1;
+}
+
+#[inline]
+fn print_needed_stats_to_one_shot(parameters: DamageParameters, health: i64) {
+    let needed_stat_to_string = |result: Option<i32>| {
+        result.map_or_else(
+            || "(unreachable)".to_owned(),
+            |value| {
+                with_comma_separators(&value.to_string())
+                    .unwrap_or_else(|| value.to_string())
+            },
+        )
+    };
+
+    println!();
+    println!(
+        "To kill your enemy in one critical hit, you need {} more Strength, {} more Crit Damage or {} more weapon Damage.",
+        needed_stat_to_string(find_needed_stat_to_one_shot(
+            parameters,
+            health,
+            |parameters| &mut parameters.strength
+        )),
+        needed_stat_to_string(find_needed_stat_to_one_shot(
+            parameters,
+            health,
+            |parameters| &mut parameters.crit_damage
+        )),
+        needed_stat_to_string(find_needed_stat_to_one_shot(
+            parameters,
+            health,
+            |parameters| &mut parameters.weapon_damage
+        ))
+    );
+}
+
+#[inline]
+#[must_use]
+fn format_damage(damage: f64) -> String {
+    let damage = f64_to_i64(damage.trunc());
+
+    with_comma_separators(&damage.to_string())
+        .unwrap_or_else(|| damage.to_string())
+}
+
+#[inline]
+#[must_use]
+fn hits_to_kill_to_string(hits_to_kill: Option<i64>) -> String {
+    hits_to_kill.map_or_else(
+        || "(infinite)".to_owned(),
+        |hits| {
+            with_comma_separators(&hits.to_string())
+                .unwrap_or_else(|| hits.to_string())
+        },
+    )
+}
+
+// Only the part of the stat coming from dungeon items is boosted.
+#[inline]
+#[must_use]
+fn apply_dungeon_stat_boost(
+    stat: i32,
+    dungeon_item_stat: i32,
+    dungeon_stat_boost_percent: i32,
+) -> f64 {
+    f64::from(dungeon_item_stat.clamp(0, stat.max(0))).mul_add(
+        f64::from(dungeon_stat_boost_percent) / 100.0,
+        f64::from(stat),
+    )
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn calculate_damage(parameters: DamageParameters) -> DamageResult {
+    let boost = parameters.dungeon_stat_boost_percent;
+
+    let weapon_damage = f64::from(BASE_WEAPON_DAMAGE)
+        + apply_dungeon_stat_boost(
+            parameters.weapon_damage,
+            if parameters.dungeon_weapon {
+                parameters.weapon_damage
+            } else {
+                0
+            },
+            boost,
+        );
+    let strength = apply_dungeon_stat_boost(
+        parameters.strength,
+        parameters.dungeon_item_strength,
+        boost,
+    );
+    let crit_damage = apply_dungeon_stat_boost(
+        parameters.crit_damage,
+        parameters.dungeon_item_crit_damage,
+        boost,
+    );
+
+    let non_crit_damage = weapon_damage
+        * (1.0 + strength / 100.0)
+        * (1.0 + parameters.additive_multiplier_percent / 100.0)
+        * parameters.multiplicative_multiplier;
+    let crit_damage = non_crit_damage * (1.0 + crit_damage / 100.0);
+
+    let crit_chance = f64::from(parameters.crit_chance.clamp(0, 100)) / 100.0;
+
+    DamageResult {
+        non_crit: non_crit_damage,
+        crit: crit_damage,
+
+        expected: (crit_damage - non_crit_damage)
+            .mul_add(crit_chance, non_crit_damage),
+    }
+}
+
+// None if the damage is not positive, since the enemy can't be killed then.
+#[inline]
+#[must_use]
+pub(crate) fn calculate_hits_to_kill(
+    health: i64,
+    damage_per_hit: f64,
+) -> Option<i64> {
+    if damage_per_hit <= 0.0 {
+        return None;
+    }
+
+    Some(f64_to_i64((i64_to_f64(health) / damage_per_hit).ceil()))
+}
+
+// Returns the least amount of the stat that needs to be added to kill an enemy
+// with the given Health in one critical hit, or None if no amount is enough.
+#[inline]
+#[must_use]
+pub(crate) fn find_needed_stat_to_one_shot(
+    parameters: DamageParameters,
+    health: i64,
+    stat: fn(&mut DamageParameters) -> &mut i32,
+) -> Option<i32> {
+    let health = i64_to_f64(health);
+
+    let kills = |extra: i32| {
+        let mut parameters = parameters;
+        let value = stat(&mut parameters);
+
+        *value = value.saturating_add(extra);
+
+        calculate_damage(parameters).crit >= health
+    };
+
+    if kills(0) {
+        return Some(0);
+    }
+
+    if !kills(i32::MAX) {
+        return None;
+    }
+
+    // Damage only goes up with more of a stat, so we can binary search.
+    let (mut low, mut high) = (0, i32::MAX);
+
+    while high - low > 1 {
+        let middle = low + (high - low) / 2;
+
+        if kills(middle) {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+
+    Some(high)
+}
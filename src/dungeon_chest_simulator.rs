@@ -0,0 +1,515 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::time::Instant;
+
+use colored::Colorize;
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::fees::fee_rates;
+use crate::prices::BazaarSide;
+use crate::prices::ask_bazaar_side;
+use crate::prices::ask_price_manually;
+use crate::prices::fetch_bazaar_prices;
+use crate::prices::fetch_lowest_bin_prices;
+use crate::rng_simulator::new_rng;
+use crate::rng_simulator::rand_f64;
+use crate::utils::ask_float_input;
+use crate::utils::ask_int_input;
+use crate::utils::format_coins_f64;
+use crate::utils::i64_to_f64;
+use crate::utils::usize_to_f64;
+use crate::utils::usize_to_i32;
+
+const SIMULATED_RUNS: usize = 100_000;
+
+const KISMET_FEATHER_ID: &str = "KISMET_FEATHER";
+
+// Every floor lists its source, if you are updating an entry, please also
+// update the source. Drop chances and chest costs are approximations.
+static FLOOR_CHESTS: LazyLock<Result<Vec<FloorChests>, serde_json::Error>> =
+    LazyLock::new(|| {
+        serde_json::from_str(include_str!("resources/dungeon_chests.json"))
+    });
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum LootMarket {
+    Bazaar,
+    Auction,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub(crate) struct ChestLoot {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) market: LootMarket,
+
+    // Chance in percent of the item being in the chest.
+    pub(crate) chance: f64,
+    #[serde(default = "default_amount")]
+    pub(crate) amount: i32,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub(crate) struct DungeonChest {
+    pub(crate) name: String,
+
+    // Least run score needed for the chest to appear.
+    pub(crate) minimum_score: i32,
+    pub(crate) cost: i64,
+
+    pub(crate) loot: Vec<ChestLoot>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub(crate) struct FloorChests {
+    pub(crate) floor: String,
+    pub(crate) source: String,
+
+    pub(crate) chests: Vec<DungeonChest>,
+}
+
+// A chest with the coin value of each possible item, ready for simulation.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct PricedChest {
+    pub(crate) name: String,
+    pub(crate) cost: f64,
+
+    // Chance in percent and coin value of each possible item.
+    pub(crate) loot: Vec<(f64, f64)>,
+}
+
+impl PricedChest {
+    #[inline]
+    #[must_use]
+    pub(crate) fn expected_profit(&self) -> f64 {
+        self.loot
+            .iter()
+            .map(|&(chance, value)| chance / 100.0 * value)
+            .sum::<f64>()
+            - self.cost
+    }
+
+    #[inline]
+    #[must_use]
+    fn roll_profit(&self, rng: &mut impl Rng) -> f64 {
+        self.loot
+            .iter()
+            .filter(|&&(chance, _)| rand_f64(rng) < chance / 100.0)
+            .map(|&(_, value)| value)
+            .sum::<f64>()
+            - self.cost
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ChestSimulation {
+    pub(crate) profit_per_run: f64,
+    pub(crate) profit_per_run_with_kismet: f64,
+
+    // Percent of runs where a Kismet Feather was used.
+    pub(crate) reroll_percent: f64,
+    // Percent of runs each chest was opened in, in the order of the chests,
+    // using Kismet Feathers if they are worth it.
+    pub(crate) opened_percent: Vec<f64>,
+}
+
+impl ChestSimulation {
+    #[inline]
+    #[must_use]
+    pub(crate) fn kismet_is_worth_it(&self) -> bool {
+        self.profit_per_run_with_kismet > self.profit_per_run
+    }
+}
+
+#[inline]
+#[must_use]
+const fn default_amount() -> i32 {
+    1
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn floor_chests() -> Option<&'static [FloorChests]> {
+    match &*FLOOR_CHESTS {
+        Ok(floors) if floors.is_empty() => {
+            eprintln!("{}", "error: dungeon chest database is empty".red());
+
+            None
+        },
+
+        Ok(floors) => Some(floors),
+
+        Err(e) => {
+            eprintln!(
+                "{}{e}",
+                "error: can't parse dungeon chest database: ".red()
+            );
+
+            None
+        },
+    }
+}
+
+#[inline]
+pub(crate) async fn dungeon_chest_simulator(
+    start_without_user_input: &mut Option<Instant>,
+) -> bool {
+    let Some(floors) = floor_chests() else {
+        return false;
+    };
+
+    let floor = select_floor(floors);
+
+    let score = ask_int_input(
+        "What score do you usually get? (use the run score predictor if you are unsure): ",
+        Some(0),
+        None,
+    );
+    let minutes_per_run = ask_float_input(
+        "How long does a run take you? (in minutes, enter 0 to skip profit per hour): ",
+        Some(0.0),
+        None,
+    );
+    let side = ask_bazaar_side(false);
+
+    *start_without_user_input = Some(Instant::now());
+
+    let chests = floor
+        .chests
+        .iter()
+        .filter(|chest| chest.minimum_score <= score)
+        .collect::<Vec<_>>();
+
+    if chests.is_empty() {
+        eprintln!(
+            "{}{score}",
+            "error: no chests are available at score ".red()
+        );
+
+        return false;
+    }
+
+    let mut asked_prices_manually = false;
+
+    let prices =
+        fetch_loot_prices(&chests, side, &mut asked_prices_manually).await;
+    let kismet_price =
+        fetch_bazaar_prices(&[KISMET_FEATHER_ID.to_owned()], BazaarSide::Buy)
+            .await
+            .and_then(|prices| prices.first().copied().flatten())
+            .unwrap_or_else(|| {
+                asked_prices_manually = true;
+
+                ask_price_manually("Kismet Feather")
+            });
+
+    // Entering prices is user input, so it's not counted.
+    if asked_prices_manually {
+        *start_without_user_input = Some(Instant::now());
+    }
+
+    let priced_chests = chests
+        .iter()
+        .map(|chest| price_chest(chest, &prices))
+        .collect::<Vec<_>>();
+
+    let simulation = simulate_chest_openings(
+        &priced_chests,
+        kismet_price,
+        SIMULATED_RUNS,
+        &mut new_rng(),
+    );
+
+    print_simulation(
+        floor,
+        &priced_chests,
+        &simulation,
+        kismet_price,
+        minutes_per_run,
+    );
+
+    true
+}
+
+#[inline]
+#[must_use]
+fn select_floor(floors: &[FloorChests]) -> &FloorChests {
+    println!();
+    println!("Select the floor: ");
+
+    for (index, floor) in floors.iter().enumerate() {
+        println!(
+            " {}. {}",
+            (index + 1).to_string().bright_blue(),
+            floor.floor
+        );
+    }
+
+    let selection = ask_int_input(
+        "Enter a number to select: ",
+        Some(1),
+        Some(usize_to_i32(floors.len())),
+    );
+
+    usize::try_from(selection - 1)
+        .ok()
+        .and_then(|index| floors.get(index))
+        .unwrap_or(&floors[0])
+}
+
+// Loot is priced at what it sells for after the fees: the lowest BIN for
+// auction items and the price at the side for Bazaar products. Prices that
+// can't be fetched are asked manually, setting asked_prices_manually.
+#[inline]
+async fn fetch_loot_prices(
+    chests: &[&DungeonChest],
+    side: BazaarSide,
+    asked_prices_manually: &mut bool,
+) -> HashMap<String, f64> {
+    let mut items = Vec::<&ChestLoot>::new();
+
+    for loot in chests.iter().flat_map(|chest| &chest.loot) {
+        if !items.iter().any(|item| item.id == loot.id) {
+            items.push(loot);
+        }
+    }
+
+    let ids_in = |market: LootMarket| {
+        items
+            .iter()
+            .filter(|loot| loot.market == market)
+            .map(|loot| loot.id.clone())
+            .collect::<Vec<_>>()
+    };
+
+    let auction_ids = ids_in(LootMarket::Auction);
+    let bazaar_ids = ids_in(LootMarket::Bazaar);
+
+    let auction_prices = fetch_lowest_bin_prices(&auction_ids, None)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|price| price.map(i64_to_f64));
+    let bazaar_prices =
+        fetch_bazaar_prices(&bazaar_ids, side).await.unwrap_or_default();
+
+    let fetched_prices = auction_ids
+        .iter()
+        .zip(auction_prices)
+        .chain(bazaar_ids.iter().zip(bazaar_prices))
+        .filter_map(|(id, price)| Some((id.as_str(), price?)))
+        .collect::<HashMap<_, _>>();
+
+    items
+        .into_iter()
+        .map(|loot| {
+            (
+                loot.id.clone(),
+                net_sale_price(
+                    loot.market,
+                    fetched_prices
+                        .get(loot.id.as_str())
+                        .copied()
+                        .unwrap_or_else(|| {
+                            *asked_prices_manually = true;
+
+                            ask_price_manually(&loot.name)
+                        }),
+                ),
+            )
+        })
+        .collect()
+}
+
+#[inline]
+#[must_use]
+fn net_sale_price(market: LootMarket, price: f64) -> f64 {
+    let rates = fee_rates();
+
+    price
+        - match market {
+            LootMarket::Bazaar => rates.bazaar_sale_fees(price),
+            LootMarket::Auction => rates.bin_sale_fees(price),
+        }
+}
+
+#[inline]
+#[must_use]
+fn price_chest(
+    chest: &DungeonChest,
+    prices: &HashMap<String, f64>,
+) -> PricedChest {
+    PricedChest {
+        name: chest.name.clone(),
+        cost: i64_to_f64(chest.cost),
+
+        loot: chest
+            .loot
+            .iter()
+            .map(|loot| {
+                (
+                    loot.chance,
+                    prices.get(&loot.id).copied().unwrap_or(0.0)
+                        * f64::from(loot.amount),
+                )
+            })
+            .collect(),
+    }
+}
+
+#[inline]
+fn print_simulation(
+    floor: &FloorChests,
+    chests: &[PricedChest],
+    simulation: &ChestSimulation,
+    kismet_price: f64,
+    minutes_per_run: f64,
+) {
+    println!();
+    println!(
+        "{} Drop chances and chest costs are approximations, and RNG Meter and Chest Keys are not taken into account. Loot is valued after the auction and Bazaar fees.",
+        "Note:".red()
+    );
+    println!("Source: {}", floor.source);
+    println!();
+    println!("Expected profit of each chest on {}:", floor.floor);
+
+    for (chest, opened_percent) in
+        chests.iter().zip(&simulation.opened_percent)
+    {
+        println!(
+            " {}: {} coins (costs {}), opened in {opened_percent:.2}% of runs",
+            chest.name,
+            format_coins_f64(chest.expected_profit()).yellow(),
+            format_coins_f64(chest.cost)
+        );
+    }
+
+    let best_profit_per_run =
+        simulation.profit_per_run.max(simulation.profit_per_run_with_kismet);
+
+    println!();
+    println!(
+        "Opening the most valuable chest every run gives {} coins per run, or {} coins using Kismet Feathers ({} coins each).",
+        format_coins_f64(simulation.profit_per_run).bright_green(),
+        format_coins_f64(simulation.profit_per_run_with_kismet).bright_green(),
+        format_coins_f64(kismet_price)
+    );
+
+    if minutes_per_run > 0.0 {
+        println!(
+            "That is {} coins per hour.",
+            format_coins_f64(best_profit_per_run * 60.0 / minutes_per_run)
+                .bright_green()
+        );
+    }
+
+    println!();
+
+    if simulation.kismet_is_worth_it() {
+        println!(
+            "Best strategy: open the most valuable chest, but reroll the chest with the highest expected profit with a Kismet Feather when nothing is worth more than its expected profit minus the Kismet Feather price. This happens in {:.2}% of runs.",
+            simulation.reroll_percent
+        );
+    } else {
+        println!(
+            "Best strategy: open the most valuable chest, Kismet Feathers are not worth it."
+        );
+    }
+}
+
+// Simulates the given amount of runs, opening the most profitable chest each
+// run, and rerolling the chest with the highest expected profit with a Kismet
+// Feather when no chest is worth more than the expected profit of a reroll.
+#[inline]
+#[must_use]
+pub(crate) fn simulate_chest_openings(
+    chests: &[PricedChest],
+    kismet_price: f64,
+    runs: usize,
+    rng: &mut impl Rng,
+) -> ChestSimulation {
+    let expected_profits =
+        chests.iter().map(PricedChest::expected_profit).collect::<Vec<_>>();
+
+    let reroll_target = expected_profits
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(index, _)| index);
+    let reroll_threshold = reroll_target
+        .and_then(|index| expected_profits.get(index))
+        .map_or(f64::NEG_INFINITY, |profit| profit - kismet_price);
+
+    let mut total_profit = 0.0;
+    let mut total_profit_with_kismet = 0.0;
+    let mut rerolls = 0;
+
+    let mut opened = vec![0; chests.len()];
+    let mut opened_with_kismet = vec![0; chests.len()];
+
+    for _ in 0..runs {
+        let mut profits = chests
+            .iter()
+            .map(|chest| chest.roll_profit(rng))
+            .collect::<Vec<_>>();
+
+        let Some((best, profit)) = find_best_chest(&profits) else {
+            break;
+        };
+
+        total_profit += profit;
+        opened[best] += 1;
+
+        match reroll_target {
+            Some(target) if profit < reroll_threshold => {
+                profits[target] = chests[target].roll_profit(rng);
+
+                if let Some((best, profit)) = find_best_chest(&profits) {
+                    total_profit_with_kismet += profit - kismet_price;
+                    opened_with_kismet[best] += 1;
+                }
+
+                rerolls += 1;
+            },
+
+            _ => {
+                total_profit_with_kismet += profit;
+                opened_with_kismet[best] += 1;
+            },
+        }
+    }
+
+    let runs = usize_to_f64(runs.max(1));
+
+    let simulation = ChestSimulation {
+        profit_per_run: total_profit / runs,
+        profit_per_run_with_kismet: total_profit_with_kismet / runs,
+
+        reroll_percent: f64::from(rerolls) / runs * 100.0,
+        opened_percent: vec![],
+    };
+
+    let opened = if simulation.kismet_is_worth_it() {
+        opened_with_kismet
+    } else {
+        opened
+    };
+
+    ChestSimulation {
+        opened_percent: opened
+            .into_iter()
+            .map(|count| f64::from(count) / runs * 100.0)
+            .collect(),
+
+        ..simulation
+    }
+}
+
+#[inline]
+#[must_use]
+fn find_best_chest(profits: &[f64]) -> Option<(usize, f64)> {
+    profits.iter().copied().enumerate().max_by(|(_, a), (_, b)| a.total_cmp(b))
+}
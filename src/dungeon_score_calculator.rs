@@ -0,0 +1,390 @@
+use std::time::Instant;
+
+use colored::Colorize;
+
+use crate::catacombs_stat_boost_calculator::get_generals_medallion_stat_boost;
+use crate::constants::DUNGEON_FLOOR_REQUIREMENTS;
+use crate::constants::S_PLUS_SCORE;
+use crate::constants::SECRETS_NEEDED_FOR_MAX_GENERALS_MEDALLION;
+use crate::profile_importer::imported_or_ask;
+use crate::utils::ask_int_input;
+use crate::utils::f64_to_i32;
+use crate::utils::usize_to_i32;
+use crate::utils::with_comma_separators;
+
+// Source: https://wiki.hypixel.net/Dungeon_Score
+// The penalties and bonuses are approximations, assuming every room is
+// cleared. Update these if the values on the wiki change.
+const MAXIMUM_COMPONENT_SCORE: i32 = 100;
+const MINIMUM_SKILL_SCORE: i32 = 20;
+const SKILL_PENALTY_PER_DEATH: i32 = 2;
+const SKILL_PENALTY_PER_FAILED_PUZZLE: i32 = 10;
+const ROOMS_EXPLORATION_SCORE: i32 = 60;
+const SECRETS_EXPLORATION_SCORE: i32 = 40;
+const MAXIMUM_CRYPTS: i32 = 5;
+const MIMIC_BONUS: i32 = 2;
+const PAUL_BONUS: i32 = 10;
+
+// Percent over the time limit up to which each rate applies, and the Speed
+// score lost per percent over the time limit.
+const SPEED_PENALTY_BRACKETS: [(f64, f64); 3] =
+    [(20.0, 0.5), (40.0, 0.25), (f64::INFINITY, 1.0 / 6.0)];
+
+// Least score needed for each rank, from best to worst.
+const SCORE_RANKS: [(i32, &str); 6] = [
+    (S_PLUS_SCORE, "S+"),
+    (270, "S"),
+    (230, "A"),
+    (160, "B"),
+    (100, "C"),
+    (0, "D"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct DungeonRun {
+    pub(crate) deaths: i32,
+    pub(crate) puzzles_failed: i32,
+
+    pub(crate) secrets_found: i32,
+    pub(crate) total_secrets: i32,
+    pub(crate) secret_percent_required: i32,
+
+    pub(crate) crypts: i32,
+    // Only Floor 6, Floor 7 and their Master Mode floors have a Mimic.
+    pub(crate) has_mimic: bool,
+    pub(crate) mimic_killed: bool,
+    pub(crate) paul: bool,
+
+    pub(crate) run_seconds: i32,
+    pub(crate) time_limit_seconds: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct DungeonScore {
+    pub(crate) skill: i32,
+    pub(crate) exploration: i32,
+    pub(crate) speed: i32,
+    pub(crate) bonus: i32,
+}
+
+impl DungeonScore {
+    #[inline]
+    #[must_use]
+    pub(crate) const fn total(self) -> i32 {
+        self.skill + self.exploration + self.speed + self.bonus
+    }
+}
+
+#[inline]
+pub(crate) fn dungeon_score_calculator(
+    start_without_user_input: &mut Option<Instant>,
+) -> bool {
+    let run = ask_dungeon_run();
+
+    let secrets = imported_or_ask(
+        "secrets",
+        |profile| profile.secrets,
+        || {
+            ask_int_input(
+                "Enter the amount of secrets you have in total, for General's Medallion progress (Enter 0 to skip): ",
+                Some(0),
+                Some(i32::MAX),
+            )
+        },
+    );
+
+    *start_without_user_input = Some(Instant::now());
+
+    let score = calculate_dungeon_score(run);
+    let total = score.total();
+
+    println!();
+    println!(
+        "{} Scores are approximations assuming every room is cleared.",
+        "Note:".red()
+    );
+    println!();
+    println!(
+        "Skill: {}, Exploration: {}, Speed: {}, Bonus: {}",
+        score.skill, score.exploration, score.speed, score.bonus
+    );
+    println!(
+        "Your run score is {}, which is rank {}.",
+        total.to_string().bright_green(),
+        get_score_rank(total).bright_green()
+    );
+
+    print_missing_for_s_plus(run, score);
+
+    if secrets > 0 {
+        print_generals_medallion_progress(secrets, run.secrets_found);
+    }
+
+    true
+}
+
+#[inline]
+#[must_use]
+fn ask_dungeon_run() -> DungeonRun {
+    println!();
+    println!("Select the floor: ");
+
+    for (index, (floor, ..)) in DUNGEON_FLOOR_REQUIREMENTS.iter().enumerate() {
+        println!(" {}. {floor}", (index + 1).to_string().bright_blue());
+    }
+
+    let selection = ask_int_input(
+        "Enter a number to select: ",
+        Some(1),
+        Some(usize_to_i32(DUNGEON_FLOOR_REQUIREMENTS.len())),
+    );
+
+    let (_, secret_percent_required, time_limit_seconds, has_mimic) =
+        usize::try_from(selection - 1)
+            .ok()
+            .and_then(|index| DUNGEON_FLOOR_REQUIREMENTS.get(index))
+            .copied()
+            .unwrap_or(DUNGEON_FLOOR_REQUIREMENTS[0]);
+
+    let deaths = ask_int_input("How many deaths?: ", Some(0), None);
+    let puzzles_failed =
+        ask_int_input("How many puzzles failed?: ", Some(0), None);
+    let total_secrets =
+        ask_int_input("How many secrets are in the dungeon?: ", Some(0), None);
+    let secrets_found = ask_int_input(
+        "How many secrets were found?: ",
+        Some(0),
+        Some(total_secrets),
+    );
+    let crypts =
+        ask_int_input("How many crypts were killed?: ", Some(0), None);
+    let mimic_killed = has_mimic
+        && ask_int_input(
+            "Was the Mimic killed? (Enter 1 for yes, 0 for no): ",
+            Some(0),
+            Some(1),
+        ) == 1;
+    let paul = ask_int_input(
+        "Is Paul's +10 score perk active? (Enter 1 for yes, 0 for no): ",
+        Some(0),
+        Some(1),
+    ) == 1;
+    let run_seconds = ask_int_input(
+        &format!(
+            "How long did the run take in seconds? (time limit is {}): ",
+            format_seconds(time_limit_seconds)
+        ),
+        Some(0),
+        None,
+    );
+
+    DungeonRun {
+        deaths,
+        puzzles_failed,
+
+        secrets_found,
+        total_secrets,
+        secret_percent_required,
+
+        crypts,
+        has_mimic,
+        mimic_killed,
+        paul,
+
+        run_seconds,
+        time_limit_seconds,
+    }
+}
+
+#[inline]
+fn print_missing_for_s_plus(run: DungeonRun, score: DungeonScore) {
+    let missing = S_PLUS_SCORE - score.total();
+
+    println!();
+
+    if missing <= 0 {
+        println!("{}", "You got S+, nothing is missing.".bright_green());
+
+        return;
+    }
+
+    println!(
+        "You need {} more score for S+. Where you lost score:",
+        missing.to_string().bright_yellow()
+    );
+
+    if score.skill < MAXIMUM_COMPONENT_SCORE {
+        println!(
+            " {} Skill from {} deaths and {} failed puzzles",
+            MAXIMUM_COMPONENT_SCORE - score.skill,
+            run.deaths,
+            run.puzzles_failed
+        );
+    }
+
+    let secrets_missing =
+        calculate_required_secrets(run).saturating_sub(run.secrets_found);
+
+    if secrets_missing > 0 {
+        println!(
+            " {} Exploration, find {secrets_missing} more secrets for the full score",
+            MAXIMUM_COMPONENT_SCORE - score.exploration
+        );
+    }
+
+    if score.speed < MAXIMUM_COMPONENT_SCORE {
+        println!(
+            " {} Speed, finish within {} for the full score",
+            MAXIMUM_COMPONENT_SCORE - score.speed,
+            format_seconds(run.time_limit_seconds)
+        );
+    }
+
+    let crypts_missing = MAXIMUM_CRYPTS.saturating_sub(run.crypts);
+
+    if crypts_missing > 0 {
+        println!(" {crypts_missing} Bonus, kill {crypts_missing} more crypts");
+    }
+
+    if run.has_mimic && !run.mimic_killed {
+        println!(" {MIMIC_BONUS} Bonus, kill the Mimic");
+    }
+
+    if !run.paul {
+        println!(" {PAUL_BONUS} Bonus, Paul's perk is not active");
+    }
+}
+
+#[inline]
+fn print_generals_medallion_progress(secrets: i32, secrets_per_run: i32) {
+    let secrets_remaining =
+        SECRETS_NEEDED_FOR_MAX_GENERALS_MEDALLION.saturating_sub(secrets);
+
+    println!();
+
+    if secrets_remaining <= 0 {
+        println!(
+            "{}",
+            "Your General's Medallion is already maxed.".bright_green()
+        );
+    } else if secrets_per_run > 0 {
+        let runs = secrets_remaining.div_ceil(secrets_per_run);
+
+        println!(
+            "Your General's Medallion gives +{}% stat boost. Finding {secrets_per_run} secrets per run, you need {} more runs to max it.",
+            get_generals_medallion_stat_boost(secrets),
+            with_comma_separators(&runs.to_string())
+                .unwrap_or_else(|| runs.to_string())
+                .bright_yellow()
+        );
+    } else {
+        println!(
+            "You need to find secrets in your runs to progress General's Medallion."
+        );
+    }
+}
+
+#[inline]
+#[must_use]
+fn format_seconds(seconds: i32) -> String {
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn calculate_required_secrets(run: DungeonRun) -> i32 {
+    (run.total_secrets.saturating_mul(run.secret_percent_required))
+        .div_ceil(100)
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn calculate_skill_score(run: DungeonRun) -> i32 {
+    MAXIMUM_COMPONENT_SCORE
+        .saturating_sub(run.deaths.saturating_mul(SKILL_PENALTY_PER_DEATH))
+        .saturating_sub(
+            run.puzzles_failed.saturating_mul(SKILL_PENALTY_PER_FAILED_PUZZLE),
+        )
+        .clamp(MINIMUM_SKILL_SCORE, MAXIMUM_COMPONENT_SCORE)
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn calculate_exploration_score(run: DungeonRun) -> i32 {
+    let required_secrets = calculate_required_secrets(run);
+
+    if required_secrets <= 0 {
+        return ROOMS_EXPLORATION_SCORE + SECRETS_EXPLORATION_SCORE;
+    }
+
+    ROOMS_EXPLORATION_SCORE
+        + SECRETS_EXPLORATION_SCORE
+            * run.secrets_found.clamp(0, required_secrets)
+            / required_secrets
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn calculate_speed_score(
+    run_seconds: i32,
+    time_limit_seconds: i32,
+) -> i32 {
+    if time_limit_seconds <= 0 || run_seconds <= time_limit_seconds {
+        return MAXIMUM_COMPONENT_SCORE;
+    }
+
+    let percent_over = f64::from(run_seconds - time_limit_seconds)
+        / f64::from(time_limit_seconds)
+        * 100.0;
+
+    let mut penalty = 0.0;
+    let mut bracket_start = 0.0;
+
+    for (bracket_end, penalty_per_percent) in SPEED_PENALTY_BRACKETS {
+        if percent_over <= bracket_start {
+            break;
+        }
+
+        penalty = (percent_over.min(bracket_end) - bracket_start)
+            .mul_add(penalty_per_percent, penalty);
+        bracket_start = bracket_end;
+    }
+
+    f64_to_i32((f64::from(MAXIMUM_COMPONENT_SCORE) - penalty).floor().max(0.0))
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn calculate_bonus_score(run: DungeonRun) -> i32 {
+    let mut bonus = run.crypts.clamp(0, MAXIMUM_CRYPTS);
+
+    if run.has_mimic && run.mimic_killed {
+        bonus += MIMIC_BONUS;
+    }
+
+    if run.paul {
+        bonus += PAUL_BONUS;
+    }
+
+    bonus
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn calculate_dungeon_score(run: DungeonRun) -> DungeonScore {
+    DungeonScore {
+        skill: calculate_skill_score(run),
+        exploration: calculate_exploration_score(run),
+        speed: calculate_speed_score(run.run_seconds, run.time_limit_seconds),
+        bonus: calculate_bonus_score(run),
+    }
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn get_score_rank(score: i32) -> &'static str {
+    SCORE_RANKS
+        .iter()
+        .find(|&&(minimum_score, _)| score >= minimum_score)
+        .map_or("D", |&(_, rank)| rank)
+}
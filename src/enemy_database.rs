@@ -0,0 +1,315 @@
+use core::cmp::Reverse;
+use core::fmt;
+use std::sync::LazyLock;
+
+use colored::Colorize;
+use serde::Deserialize;
+
+use crate::utils::ask_int_input;
+use crate::utils::ask_string_input;
+use crate::utils::fuzzy_match_score;
+use crate::utils::usize_to_i32;
+use crate::utils::with_comma_separators;
+
+// Every entry lists its source, if you are updating or adding an entry, please
+// also update the source.
+static ENEMIES: LazyLock<Result<Vec<Enemy>, serde_json::Error>> =
+    LazyLock::new(|| {
+        serde_json::from_str(include_str!("resources/enemies.json"))
+    });
+
+#[derive(
+    Debug, Deserialize, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash,
+)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum DamageType {
+    Melee,
+    Ranged,
+    Magic,
+    True,
+}
+
+impl DamageType {
+    #[inline]
+    #[must_use]
+    pub(crate) const fn ignores_defense(self) -> bool {
+        matches!(self, Self::True)
+    }
+}
+
+impl fmt::Display for DamageType {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Melee => "Melee",
+            Self::Ranged => "Ranged",
+            Self::Magic => "Magic",
+            Self::True => "True",
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub(crate) struct Attack {
+    pub(crate) name: String,
+
+    pub(crate) damage: i32,
+    pub(crate) damage_type: DamageType,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub(crate) struct Enemy {
+    pub(crate) name: String,
+    pub(crate) category: String,
+
+    // None if unknown or if it isn't a single number, e.g. bosses that die
+    // after a fixed amount of hits.
+    pub(crate) health: Option<i64>,
+
+    pub(crate) source: String,
+    pub(crate) note: Option<String>,
+
+    pub(crate) attacks: Vec<Attack>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
+pub(crate) struct SelectedEnemy {
+    pub(crate) damage_per_hit: i32,
+    pub(crate) ignores_defense: bool,
+}
+
+// Returns None after printing the error if the bundled database can't be
+// parsed, the tools using it can't work without it.
+#[inline]
+#[must_use]
+pub(crate) fn enemies() -> Option<&'static [Enemy]> {
+    match &*ENEMIES {
+        Ok(enemies) => Some(enemies),
+
+        Err(e) => {
+            eprintln!("{}{e}", "error: can't parse enemy database: ".red());
+
+            None
+        },
+    }
+}
+
+// Returns enemies matching the query, best matches first. An empty query
+// matches every enemy in database order.
+#[inline]
+#[must_use]
+pub(crate) fn search_enemies<'a>(
+    enemies: &'a [Enemy],
+    query: &str,
+) -> Vec<&'a Enemy> {
+    let mut matches = enemies
+        .iter()
+        .filter_map(|enemy| {
+            fuzzy_match_score(
+                query,
+                &format!("{} {}", enemy.category, enemy.name),
+            )
+            .map(|score| (score, enemy))
+        })
+        .collect::<Vec<_>>();
+
+    // Stable sort, so equally good matches keep the database order.
+    matches.sort_by_key(|&(score, _)| Reverse(score));
+
+    matches.into_iter().map(|(_, enemy)| enemy).collect()
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn select_enemy() -> Option<SelectedEnemy> {
+    Some(search_and_select_enemy(enemies()?).map_or_else(
+        || SelectedEnemy {
+            damage_per_hit: ask_int_input(
+                "Enter your enemy's damage: ",
+                Some(0),
+                None,
+            ),
+            ignores_defense: false,
+        },
+        select_attack,
+    ))
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn select_enemy_health() -> Option<i64> {
+    if let Some(enemy) = search_and_select_enemy(enemies()?) {
+        println!();
+        println!("Source: {}", enemy.source);
+
+        if let Some(note) = &enemy.note {
+            println!("{} {note}", "Note:".red());
+        }
+
+        if let Some(health) = enemy.health {
+            println!(
+                "{} Health values are approximations, many of them can't be verified in game.",
+                "Note:".red()
+            );
+            println!(
+                "{} has {} Health.",
+                enemy.name,
+                with_comma_separators(&health.to_string())
+                    .unwrap_or_else(|| health.to_string())
+            );
+
+            return Some(health);
+        }
+
+        println!(
+            "{}",
+            "The enemy database doesn't know the Health of this enemy."
+                .bright_red()
+        );
+    }
+
+    Some(i64::from(ask_int_input(
+        "Enter your enemy's Health: ",
+        Some(1),
+        None,
+    )))
+}
+
+// Returns None if the user wants to enter their enemy's stats manually.
+#[inline]
+#[must_use]
+fn search_and_select_enemy(
+    enemies: &'static [Enemy],
+) -> Option<&'static Enemy> {
+    loop {
+        println!();
+
+        let query = ask_string_input(
+            "Search for your enemy (e.g. 'f7 fels', leave empty to list all): ",
+        );
+        let matches = search_enemies(enemies, &query);
+
+        println!();
+
+        if matches.is_empty() {
+            println!("{}{query}", "No enemies found matching ".bright_red());
+        } else {
+            println!("Select your enemy: ");
+        }
+
+        for (index, enemy) in matches.iter().enumerate() {
+            println!(
+                " {}. {} ({})",
+                (index + 1).to_string().bright_blue(),
+                enemy.name,
+                enemy.category
+            );
+        }
+
+        let custom_selection = usize_to_i32(matches.len()) + 1;
+
+        println!(" {}. Custom", custom_selection.to_string().bright_blue());
+        println!(
+            " {}. Search again",
+            (custom_selection + 1).to_string().bright_blue()
+        );
+
+        let selection = ask_int_input(
+            "Enter a number to select: ",
+            Some(1),
+            Some(custom_selection + 1),
+        );
+
+        if selection == custom_selection {
+            return None;
+        }
+
+        if let Ok(index) = usize::try_from(selection - 1)
+            && let Some(enemy) = matches.get(index)
+        {
+            return Some(enemy);
+        }
+    }
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn select_attack(enemy: &Enemy) -> SelectedEnemy {
+    println!();
+    println!("Source: {}", enemy.source);
+
+    if let Some(note) = &enemy.note {
+        println!("{} {note}", "Note:".red());
+    }
+
+    println!(
+        "{} Attack damages are approximations, many of them can't be verified in game.",
+        "Note:".red()
+    );
+
+    // Attacks can only be combined if Defense applies to all or none of
+    // them.
+    let first_ignores_defense = enemy
+        .attacks
+        .first()
+        .is_some_and(|attack| attack.damage_type.ignores_defense());
+    let can_combine_attacks = enemy.attacks.len() > 1
+        && enemy.attacks.iter().all(|attack| {
+            attack.damage_type.ignores_defense() == first_ignores_defense
+        });
+
+    if enemy.attacks.len() > 1 {
+        println!();
+        println!("Select the attack: ");
+
+        for (index, attack) in enemy.attacks.iter().enumerate() {
+            print_attack(index + 1, attack);
+        }
+
+        let mut maximum_selection = usize_to_i32(enemy.attacks.len());
+
+        if can_combine_attacks {
+            maximum_selection += 1;
+
+            println!(
+                " {}. All of the above at once",
+                maximum_selection.to_string().bright_blue()
+            );
+        }
+
+        let selection = ask_int_input(
+            "Enter a number to select: ",
+            Some(1),
+            Some(maximum_selection),
+        );
+
+        if let Ok(index) = usize::try_from(selection - 1)
+            && let Some(attack) = enemy.attacks.get(index)
+        {
+            return SelectedEnemy {
+                damage_per_hit: attack.damage,
+                ignores_defense: attack.damage_type.ignores_defense(),
+            };
+        }
+    }
+
+    SelectedEnemy {
+        damage_per_hit: enemy.attacks.iter().map(|attack| attack.damage).sum(),
+        ignores_defense: enemy
+            .attacks
+            .iter()
+            .any(|attack| attack.damage_type.ignores_defense()),
+    }
+}
+
+#[inline]
+fn print_attack(number: usize, attack: &Attack) {
+    println!(
+        " {}. {} ({} {} damage)",
+        number.to_string().bright_blue(),
+        attack.name,
+        with_comma_separators(&attack.damage.to_string())
+            .unwrap_or_else(|| attack.damage.to_string()),
+        attack.damage_type
+    );
+}
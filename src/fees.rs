@@ -0,0 +1,151 @@
+use std::sync::OnceLock;
+
+use colored::Colorize;
+
+use crate::prices::BazaarSide;
+use crate::prices::fetch_bazaar_products;
+use crate::prices::fetch_lowest_bin_prices;
+use crate::utils::format_coins_f64;
+use crate::utils::i64_to_f64;
+
+static FEE_RATES: OnceLock<FeeRates> = OnceLock::new();
+
+const LARGE_AUCTION_PRICE: f64 = 10_000_000.0;
+const HUGE_AUCTION_PRICE: f64 = 100_000_000.0;
+const CLAIM_TAX_MINIMUM_PRICE: f64 = 1_000_000.0;
+
+// Fees taken by Hypixel when selling, in percent of the price. Buying has no
+// fees. The defaults are approximations of the current rates, they change
+// with game updates, so they can be configured.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct FeeRates {
+    // Paid when creating a BIN auction, for prices under 10M, under 100M and
+    // 100M or more.
+    pub(crate) bin_listing_fee_percents: [f64; 3],
+    // Taken from the coins of sold auctions over 1M when claiming them.
+    pub(crate) auction_claim_tax_percent: f64,
+    // Taken from the coins of sold Bazaar products.
+    pub(crate) bazaar_tax_percent: f64,
+}
+
+impl Default for FeeRates {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            bin_listing_fee_percents: [1.0, 2.0, 2.5],
+            auction_claim_tax_percent: 1.0,
+            bazaar_tax_percent: 1.25,
+        }
+    }
+}
+
+impl FeeRates {
+    // The listing fee and the claim tax of selling an item with a BIN auction.
+    #[inline]
+    #[must_use]
+    pub(crate) fn bin_sale_fees(&self, price: f64) -> f64 {
+        let [fee_percent, large_fee_percent, huge_fee_percent] =
+            self.bin_listing_fee_percents;
+
+        let listing_fee_percent = if price >= HUGE_AUCTION_PRICE {
+            huge_fee_percent
+        } else if price >= LARGE_AUCTION_PRICE {
+            large_fee_percent
+        } else {
+            fee_percent
+        };
+
+        let claim_tax_percent = if price > CLAIM_TAX_MINIMUM_PRICE {
+            self.auction_claim_tax_percent
+        } else {
+            0.0
+        };
+
+        price * (listing_fee_percent + claim_tax_percent) / 100.0
+    }
+
+    #[inline]
+    #[must_use]
+    pub(crate) fn bazaar_sale_fees(&self, price: f64) -> f64 {
+        price * self.bazaar_tax_percent / 100.0
+    }
+}
+
+#[inline]
+pub(crate) fn configure_fee_rates(rates: FeeRates) {
+    if FEE_RATES.set(rates).is_err() {
+        eprintln!(
+            "{}",
+            "warning: the fee rates are already configured".yellow()
+        );
+    }
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn fee_rates() -> FeeRates {
+    *FEE_RATES.get_or_init(FeeRates::default)
+}
+
+// Prints what selling the items would give, before and after the fees, both
+// with a BIN auction and on the Bazaar, instantly and with a sell offer.
+#[inline]
+pub(crate) async fn print_resell_prices(ids: &[String]) -> bool {
+    let lowest_bins = fetch_lowest_bin_prices(ids, None).await;
+    let bazaar_products = fetch_bazaar_products(ids).await;
+
+    if lowest_bins.is_none() && bazaar_products.is_none() {
+        eprintln!("{}", "error: can't fetch prices".red());
+
+        return false;
+    }
+
+    let rates = fee_rates();
+
+    for (index, id) in ids.iter().enumerate() {
+        println!("{}:", id.bright_blue());
+
+        let lowest_bin = lowest_bins
+            .as_ref()
+            .and_then(|prices| prices.get(index).copied().flatten());
+        let bazaar_product = bazaar_products
+            .as_ref()
+            .and_then(|products| products.get(index).copied().flatten());
+
+        if let Some(price) = lowest_bin.map(i64_to_f64) {
+            print_resell_price(
+                "BIN auction at the lowest BIN",
+                price,
+                rates.bin_sale_fees(price),
+            );
+        }
+
+        for side in [BazaarSide::Sell, BazaarSide::SellOffer] {
+            if let Some(price) =
+                bazaar_product.and_then(|product| product.price(side))
+            {
+                print_resell_price(
+                    &format!("Bazaar {}", side.name().to_lowercase()),
+                    price,
+                    rates.bazaar_sale_fees(price),
+                );
+            }
+        }
+
+        if lowest_bin.is_none() && bazaar_product.is_none() {
+            println!(" {}", "No prices found.".bright_red());
+        }
+    }
+
+    true
+}
+
+#[inline]
+pub(crate) fn print_resell_price(market: &str, price: f64, fees: f64) {
+    println!(
+        " {market}: {} coins gross, {} coins after {} coins of fees",
+        format_coins_f64(price),
+        format_coins_f64(price - fees).bright_green(),
+        format_coins_f64(fees).yellow()
+    );
+}
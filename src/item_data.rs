@@ -0,0 +1,52 @@
+use std::io::Read as _;
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use colored::Colorize;
+use flate2::read::GzDecoder;
+
+// NBT string tag named "id", holding the SkyBlock item ID in the item's
+// ExtraAttributes. The Minecraft item ID is a short, so it never matches.
+const ITEM_ID_TAG: &[u8] = &[8, 0, 2, b'i', b'd'];
+
+// Item data in the API is base64 encoded gzipped NBT, returns the NBT bytes.
+#[inline]
+#[must_use]
+pub(crate) fn decompress_item_data(item_data: &str) -> Option<Vec<u8>> {
+    let compressed = match STANDARD.decode(item_data) {
+        Ok(compressed) => compressed,
+
+        Err(e) => {
+            eprintln!("{}{e}", "error: can't decode item data: ".red());
+
+            return None;
+        },
+    };
+
+    let mut nbt = Vec::new();
+
+    if let Err(e) = GzDecoder::new(compressed.as_slice()).read_to_end(&mut nbt)
+    {
+        eprintln!("{}{e}", "error: can't decompress item data: ".red());
+
+        return None;
+    }
+
+    Some(nbt)
+}
+
+// Returns the SkyBlock item ID of the first item in the NBT, i.e.
+// MASTER_SKULL_TIER_1, without fully parsing the NBT.
+#[inline]
+#[must_use]
+pub(crate) fn find_item_id(nbt: &[u8]) -> Option<String> {
+    let tag_start = nbt
+        .windows(ITEM_ID_TAG.len())
+        .position(|window| window == ITEM_ID_TAG)?;
+
+    let value = nbt.get(tag_start + ITEM_ID_TAG.len()..)?;
+    let (&length, value) = value.split_first_chunk()?;
+    let length = usize::from(u16::from_be_bytes(length));
+
+    String::from_utf8(value.get(..length)?.to_vec()).ok()
+}
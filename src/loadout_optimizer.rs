@@ -0,0 +1,474 @@
+use core::cmp;
+use core::cmp::Ordering;
+
+use std::path::Path;
+use std::time::Instant;
+
+use colored::Colorize;
+
+use crate::enemy_database::SelectedEnemy;
+use crate::enemy_database::select_enemy;
+use crate::survivability_calculator::ask_defense;
+use crate::survivability_calculator::calculate_effective_health;
+use crate::survivability_calculator::calculate_hits_to_die;
+use crate::survivability_calculator::calculate_wither_shield_health;
+use crate::utils::ask_int_input;
+use crate::utils::ask_string_input;
+use crate::utils::read_file;
+use crate::utils::with_comma_separators;
+
+const MAXIMUM_RANKED_LOADOUTS: usize = 10;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Stats {
+    pub(crate) health: i32,
+    pub(crate) defense: i32,
+    pub(crate) crit_damage: i32,
+}
+
+impl Stats {
+    #[inline]
+    #[must_use]
+    pub(crate) const fn saturating_add(self, other: Self) -> Self {
+        Self {
+            health: self.health.saturating_add(other.health),
+            defense: self.defense.saturating_add(other.defense),
+            crit_damage: self.crit_damage.saturating_add(other.crit_damage),
+        }
+    }
+
+    // Defense does nothing against enemies dealing true damage, so it's
+    // counted as 0 against them.
+    #[inline]
+    #[must_use]
+    pub(crate) fn effective_health(
+        self,
+        uses_wither_shield: bool,
+        ignores_defense: bool,
+    ) -> i32 {
+        let wither_shield_health = if uses_wither_shield {
+            calculate_wither_shield_health(self.crit_damage)
+        } else {
+            0
+        };
+
+        calculate_effective_health(
+            self.health.saturating_add(wither_shield_health),
+            if ignores_defense { 0 } else { self.defense },
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct LoadoutCandidate {
+    pub(crate) slot: String,
+    pub(crate) name: String,
+
+    pub(crate) stats: Stats,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum LoadoutGoal {
+    MaximumEffectiveHealth,
+    // Least amount of items that survive the given amount of hits.
+    SurviveHits(i32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Loadout<'a> {
+    pub(crate) items: Vec<&'a LoadoutCandidate>,
+
+    pub(crate) stats: Stats,
+    pub(crate) effective_health: i32,
+}
+
+struct LoadoutSearch<'a> {
+    slots: Vec<Vec<&'a LoadoutCandidate>>,
+
+    // Most Health, Defense and Crit Damage the slots starting from the index
+    // can add, used to skip combinations that can't make it into the ranking.
+    remaining_maximums: Vec<Stats>,
+
+    goal: LoadoutGoal,
+    enemy: SelectedEnemy,
+    uses_wither_shield: bool,
+
+    ranked: Vec<Loadout<'a>>,
+}
+
+impl<'a> LoadoutSearch<'a> {
+    #[inline]
+    fn survives(&self, effective_health: i32) -> bool {
+        match self.goal {
+            LoadoutGoal::MaximumEffectiveHealth => true,
+
+            LoadoutGoal::SurviveHits(hits) =>
+                calculate_hits_to_die(
+                    effective_health,
+                    self.enemy.damage_per_hit,
+                )
+                .trunc()
+                    > f64::from(hits),
+        }
+    }
+
+    #[inline]
+    fn can_make_it_into_ranking(
+        &self,
+        items: usize,
+        maximum_effective_health: i32,
+    ) -> bool {
+        if !self.survives(maximum_effective_health) {
+            return false;
+        }
+
+        if self.ranked.len() < MAXIMUM_RANKED_LOADOUTS {
+            return true;
+        }
+
+        self.ranked.last().is_none_or(|worst| match self.goal {
+            LoadoutGoal::MaximumEffectiveHealth =>
+                maximum_effective_health > worst.effective_health,
+
+            // Items can only be added, never removed, as we go deeper.
+            LoadoutGoal::SurviveHits(_) =>
+                items < worst.items.len()
+                    || (items == worst.items.len()
+                        && maximum_effective_health > worst.effective_health),
+        })
+    }
+
+    #[inline]
+    fn effective_health(&self, stats: Stats) -> i32 {
+        stats.effective_health(
+            self.uses_wither_shield,
+            self.enemy.ignores_defense,
+        )
+    }
+
+    #[inline]
+    fn search(
+        &mut self,
+        slot_index: usize,
+        items: &mut Vec<&'a LoadoutCandidate>,
+        stats: Stats,
+    ) {
+        let maximum_stats = self
+            .remaining_maximums
+            .get(slot_index)
+            .map_or(stats, |&maximums| stats.saturating_add(maximums));
+
+        if !self.can_make_it_into_ranking(
+            items.len(),
+            self.effective_health(maximum_stats),
+        ) {
+            return;
+        }
+
+        let Some(candidates) = self.slots.get(slot_index) else {
+            self.rank(Loadout {
+                items: items.clone(),
+
+                stats,
+                effective_health: self.effective_health(stats),
+            });
+
+            return;
+        };
+
+        // Cloning the references is cheap and lets us borrow self mutably.
+        let candidates = candidates.clone();
+
+        for candidate in candidates {
+            items.push(candidate);
+            self.search(
+                slot_index + 1,
+                items,
+                stats.saturating_add(candidate.stats),
+            );
+            let _ = items.pop();
+        }
+
+        // Leaving the slot empty is an option when looking for the least
+        // amount of gear.
+        if matches!(self.goal, LoadoutGoal::SurviveHits(_)) {
+            self.search(slot_index + 1, items, stats);
+        }
+    }
+
+    #[inline]
+    fn rank(&mut self, loadout: Loadout<'a>) {
+        let position = self
+            .ranked
+            .iter()
+            .position(|ranked| {
+                compare_loadouts(self.goal, &loadout, ranked) == Ordering::Less
+            })
+            .unwrap_or(self.ranked.len());
+
+        self.ranked.insert(position, loadout);
+        self.ranked.truncate(MAXIMUM_RANKED_LOADOUTS);
+    }
+}
+
+// Less means better.
+#[inline]
+#[must_use]
+fn compare_loadouts(
+    goal: LoadoutGoal,
+    loadout: &Loadout<'_>,
+    other: &Loadout<'_>,
+) -> Ordering {
+    let by_effective_health =
+        other.effective_health.cmp(&loadout.effective_health);
+
+    match goal {
+        LoadoutGoal::MaximumEffectiveHealth => by_effective_health,
+
+        LoadoutGoal::SurviveHits(_) => loadout
+            .items
+            .len()
+            .cmp(&other.items.len())
+            .then(by_effective_health),
+    }
+}
+
+// Parses candidates from CSV text in the form of
+// slot,name,health,defense,crit_damage. Empty lines, lines starting with #
+// and a header line starting with slot are skipped.
+#[inline]
+pub(crate) fn parse_loadout_candidates(
+    text: &str,
+) -> Result<Vec<LoadoutCandidate>, String> {
+    let mut candidates = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty()
+            || line.starts_with('#')
+            || (index == 0 && line.to_lowercase().starts_with("slot"))
+        {
+            continue;
+        }
+
+        let columns = line.split(',').map(str::trim).collect::<Vec<_>>();
+
+        let [slot, name, health, defense, crit_damage] = columns.as_slice()
+        else {
+            return Err(format!(
+                "line {}: expected 5 columns (slot,name,health,defense,crit_damage), got {}",
+                index + 1,
+                columns.len()
+            ));
+        };
+
+        let parse = |column: &str, value: &str| {
+            value.parse::<i32>().map_err(|e| {
+                format!("line {}: invalid {column} '{value}': {e}", index + 1)
+            })
+        };
+
+        candidates.push(LoadoutCandidate {
+            slot: (*slot).to_owned(),
+            name: (*name).to_owned(),
+
+            stats: Stats {
+                health: parse("health", health)?,
+                defense: parse("defense", defense)?,
+                crit_damage: parse("crit_damage", crit_damage)?,
+            },
+        });
+    }
+
+    Ok(candidates)
+}
+
+// Returns the best loadouts for the goal, best first. Every slot gets one of
+// its candidates, except when surviving hits, where slots can also be left
+// empty.
+#[inline]
+#[must_use]
+pub(crate) fn optimize_loadouts(
+    base_stats: Stats,
+    candidates: &[LoadoutCandidate],
+    goal: LoadoutGoal,
+    enemy: SelectedEnemy,
+    uses_wither_shield: bool,
+) -> Vec<Loadout<'_>> {
+    let mut slots: Vec<(&str, Vec<&LoadoutCandidate>)> = Vec::new();
+
+    for candidate in candidates {
+        if let Some((_, slot_candidates)) =
+            slots.iter_mut().find(|(slot, _)| *slot == candidate.slot)
+        {
+            slot_candidates.push(candidate);
+        } else {
+            slots.push((&candidate.slot, vec![candidate]));
+        }
+    }
+
+    let slots = slots
+        .into_iter()
+        .map(|(_, slot_candidates)| slot_candidates)
+        .collect::<Vec<_>>();
+
+    let mut remaining_maximums = vec![Stats::default(); slots.len()];
+    let mut maximums = Stats::default();
+
+    for (index, slot_candidates) in slots.iter().enumerate().rev() {
+        let maximum_of = |stat: fn(&Stats) -> i32| {
+            slot_candidates
+                .iter()
+                .map(|candidate| stat(&candidate.stats))
+                .fold(0, cmp::max)
+        };
+
+        maximums = maximums.saturating_add(Stats {
+            health: maximum_of(|stats| stats.health),
+            defense: maximum_of(|stats| stats.defense),
+            crit_damage: maximum_of(|stats| stats.crit_damage),
+        });
+
+        if let Some(remaining_maximum) = remaining_maximums.get_mut(index) {
+            *remaining_maximum = maximums;
+        }
+    }
+
+    let mut search = LoadoutSearch {
+        slots,
+        remaining_maximums,
+
+        goal,
+        enemy,
+        uses_wither_shield,
+
+        ranked: Vec::with_capacity(MAXIMUM_RANKED_LOADOUTS + 1),
+    };
+
+    search.search(0, &mut Vec::new(), base_stats);
+
+    search.ranked
+}
+
+#[inline]
+pub(crate) fn loadout_optimizer(
+    start_without_user_input: &mut Option<Instant>,
+) -> bool {
+    let Some(enemy) = select_enemy() else {
+        return false;
+    };
+
+    let base_stats = Stats {
+        health: ask_int_input(
+            "What's your Health without the candidate items?: ",
+            Some(0),
+            None,
+        ),
+        defense: ask_defense(enemy),
+        crit_damage: ask_int_input(
+            "What's your Crit Damage without the candidate items?: ",
+            Some(0),
+            None,
+        ),
+    };
+
+    println!();
+    println!(" {}. With Wither Shield", "1".bright_blue());
+    println!(" {}. Without Wither Shield", "2".bright_blue());
+
+    let uses_wither_shield =
+        ask_int_input("Enter a number to select: ", Some(1), Some(2)) == 1;
+
+    println!();
+    println!(" {}. Maximum Effective Health", "1".bright_blue());
+    println!(
+        " {}. Least amount of gear to survive a number of hits",
+        "2".bright_blue()
+    );
+
+    let goal_selection =
+        ask_int_input("Enter a number to select: ", Some(1), Some(2));
+
+    let goal = if goal_selection == 1 {
+        LoadoutGoal::MaximumEffectiveHealth
+    } else {
+        LoadoutGoal::SurviveHits(ask_int_input(
+            "How many hits do you want to survive?: ",
+            Some(1),
+            None,
+        ))
+    };
+
+    let path = ask_string_input(
+        "Enter the path of the CSV file with your candidate items (slot,name,health,defense,crit_damage per line): ",
+    );
+
+    *start_without_user_input = Some(Instant::now());
+
+    let Some(text) = read_file(Path::new(&path)) else {
+        return false;
+    };
+
+    let candidates = match parse_loadout_candidates(&text) {
+        Ok(candidates) => candidates,
+
+        Err(e) => {
+            eprintln!("{}{e}", "error: can't parse candidates: ".red());
+
+            return false;
+        },
+    };
+
+    let loadouts = optimize_loadouts(
+        base_stats,
+        &candidates,
+        goal,
+        enemy,
+        uses_wither_shield,
+    );
+
+    println!();
+
+    if loadouts.is_empty() {
+        println!(
+            "{}",
+            "None of the loadouts can survive that many hits.".bright_red()
+        );
+
+        return true;
+    }
+
+    println!("Best loadouts:");
+
+    for (index, loadout) in loadouts.iter().enumerate() {
+        print_loadout(index + 1, loadout, enemy.damage_per_hit);
+    }
+
+    true
+}
+
+#[inline]
+fn print_loadout(
+    rank: usize,
+    loadout: &Loadout<'_>,
+    enemy_damage_per_hit: i32,
+) {
+    println!();
+    println!(
+        " {}. {} Effective Health, taking {} hits to die ({} Health, {} Defense, {} Crit Damage)",
+        rank.to_string().bright_blue(),
+        with_comma_separators(&loadout.effective_health.to_string())
+            .unwrap_or_else(|| loadout.effective_health.to_string())
+            .yellow(),
+        calculate_hits_to_die(loadout.effective_health, enemy_damage_per_hit)
+            .trunc(),
+        loadout.stats.health,
+        loadout.stats.defense,
+        loadout.stats.crit_damage
+    );
+
+    for item in &loadout.items {
+        println!("    {}: {}", item.slot, item.name);
+    }
+}
@@ -40,6 +40,7 @@
 #![warn(dead_code)]
 #![warn(variant_size_differences)]
 #![feature(stmt_expr_attributes)]
+#![feature(int_roundings)]
 //#![feature(new_range_api)]
 
 use std::collections::HashMap;
@@ -55,18 +56,38 @@
 
 mod minecraft_launcher_launcher;
 
+mod arguments;
 mod constants;
 mod utils;
 
+mod catacombs_level_planner;
 mod catacombs_stat_boost_calculator;
+mod dungeon_chest_simulator;
+mod dungeon_score_calculator;
+mod fees;
+mod item_data;
 mod master_skull_upgrade_helper;
+mod price_cache;
+mod price_history;
+mod price_providers;
+mod price_watcher;
+mod prices;
+mod profile_importer;
+mod star_cost_calculator;
+mod tiered_upgrade_planner;
 
 mod rng_simulator;
 
+mod enemy_database;
+mod loadout_optimizer;
+mod party_buffs;
 mod survivability_calculator;
 
+mod damage_calculator;
+
 mod slayer_kill_goal_watcher;
 
+mod class_average_planner;
 mod skill_average_helper;
 
 #[cfg(test)]
@@ -110,6 +131,10 @@ fn print_selections() {
     println!(" {}. Survivability Calculator", "4".bright_blue());
     println!(" {}. Slayer kill goal watcher", "5".bright_blue());
     println!(" {}. Skill average helper", "6".bright_blue());
+    println!(" {}. Damage Calculator", "7".bright_blue());
+    println!(" {}. Class average planner", "8".bright_blue());
+    println!(" {}. Dungeon chest simulator", "9".bright_blue());
+    println!(" {}. Tiered item upgrade planner", "10".bright_blue());
 
     println!();
 }
@@ -127,7 +152,8 @@ async fn handle_selection(
             .await,
         2 => catacombs_stat_boost_calculator::catacombs_stat_boost_calculator(
             start_without_user_input,
-        ),
+        )
+        .await,
         3 => rng_simulator::rng_simulator(start_without_user_input),
         4 => survivability_calculator::survivability_calculator(
             start_without_user_input,
@@ -138,6 +164,18 @@ async fn handle_selection(
         6 => skill_average_helper::skill_average_helper(
             start_without_user_input,
         ),
+        7 => damage_calculator::damage_calculator(start_without_user_input),
+        8 => class_average_planner::class_average_planner(
+            start_without_user_input,
+        ),
+        9 => dungeon_chest_simulator::dungeon_chest_simulator(
+            start_without_user_input,
+        )
+        .await,
+        10 => tiered_upgrade_planner::tiered_upgrade_planner(
+            start_without_user_input,
+        )
+        .await,
         _ => {
             eprintln!(
                 "{}",
@@ -148,6 +186,21 @@ async fn handle_selection(
     }
 }
 
+#[inline]
+async fn run_command(command: arguments::Command) -> bool {
+    match command {
+        arguments::Command::RefreshPrices => prices::refresh_prices().await,
+        arguments::Command::ResellPrices(ids) =>
+            fees::print_resell_prices(&ids).await,
+        arguments::Command::BazaarProducts(ids) =>
+            prices::print_bazaar_products(&ids).await,
+        arguments::Command::WatchPrices(file) =>
+            price_watcher::watch_prices(file.as_deref()).await,
+        arguments::Command::PriceHistory(id, window_seconds) =>
+            price_history::print_price_history(&id, window_seconds),
+    }
+}
+
 #[tokio::main]
 #[inline]
 async fn main() -> ExitCode {
@@ -178,10 +231,35 @@ async fn main() -> ExitCode {
                     );
                 }
 
-                eprintln!("{}{argument}", "invalid argument: ".red());
+                let Some(arguments) = arguments::parse_arguments(&args[1..])
+                else {
+                    return ExitCode::FAILURE; // Exit because providing
+                    // invalid arguments should not fall through
+                };
+
+                if let Some(provider) = arguments.price_provider {
+                    prices::select_price_provider(provider);
+                }
 
-                return ExitCode::FAILURE; // Exit because providing invalid
-                // arguments should not fall through
+                prices::configure_price_cache(arguments.price_cache);
+                fees::configure_fee_rates(arguments.fee_rates);
+                price_history::configure_price_smoothing(
+                    arguments.price_smoothing_window,
+                );
+
+                if let Some(command) = arguments.command {
+                    return if run_command(command).await {
+                        ExitCode::SUCCESS
+                    } else {
+                        ExitCode::FAILURE
+                    };
+                }
+
+                if let Some(profile) = arguments.profile
+                    && !profile_importer::import_profile(&profile).await
+                {
+                    return ExitCode::FAILURE;
+                }
             } // No arguments given, fall through to hypixel skyblock tools
 
             if binary_file_name == "minecraft-launcher" {
@@ -208,7 +286,7 @@ async fn main() -> ExitCode {
     print_selections();
 
     let selection =
-        utils::ask_int_input("Enter a number to select: ", Some(1), Some(6));
+        utils::ask_int_input("Enter a number to select: ", Some(1), Some(10));
 
     if !handle_selection(selection, start_without_user_input).await {
         eprintln!("Exiting with failure exit code");
@@ -0,0 +1,337 @@
+use core::fmt;
+
+use std::time::Instant;
+
+use colored::Colorize;
+
+use crate::enemy_database::enemies;
+use crate::enemy_database::select_attack;
+use crate::survivability_calculator::ask_defense;
+use crate::survivability_calculator::calculate_effective_health;
+use crate::survivability_calculator::calculate_hits_to_die;
+use crate::survivability_calculator::calculate_wither_shield_health;
+use crate::utils::ask_int_input;
+use crate::utils::f64_to_i32;
+use crate::utils::usize_to_i32;
+use crate::utils::with_comma_separators;
+
+const MAXIMUM_CLASS_LEVEL: i32 = 50;
+const MAXIMUM_TEAMMATES: i32 = 4;
+
+// Source: https://wiki.hypixel.net/Dungeon_Classes
+// The buffs are approximations at class level 50, scaled down linearly for
+// lower levels. Update these if the values on the wiki change.
+const HEALING_AURA_HEAL_PERCENT_PER_SECOND: f64 = 2.0;
+const HEALING_ORBS_HEAL_PERCENT_PER_SECOND: f64 = 1.0;
+const WISH_ABSORPTION_PERCENT: f64 = 20.0;
+const TANK_DAMAGE_REDUCTION_PERCENT: f64 = 15.0;
+const TANK_ABSORPTION_PERCENT: f64 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum DungeonClass {
+    Healer,
+    Mage,
+    Berserk,
+    Archer,
+    Tank,
+}
+
+pub(crate) const ALL_DUNGEON_CLASSES: [DungeonClass; 5] = [
+    DungeonClass::Healer,
+    DungeonClass::Mage,
+    DungeonClass::Berserk,
+    DungeonClass::Archer,
+    DungeonClass::Tank,
+];
+
+impl fmt::Display for DungeonClass {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Healer => "Healer",
+            Self::Mage => "Mage",
+            Self::Berserk => "Berserk",
+            Self::Archer => "Archer",
+            Self::Tank => "Tank",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct PartyMember {
+    pub(crate) class: DungeonClass,
+    pub(crate) class_level: i32,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub(crate) struct PartyBuffs {
+    pub(crate) healing_per_second: f64,
+    pub(crate) damage_reduction_percent: f64,
+    pub(crate) absorption: i32,
+}
+
+impl PartyBuffs {
+    // Healing and absorption stack additively, damage reduction stacks
+    // multiplicatively.
+    #[inline]
+    #[must_use]
+    pub(crate) fn combine(self, other: Self) -> Self {
+        Self {
+            healing_per_second: self.healing_per_second
+                + other.healing_per_second,
+            damage_reduction_percent: 100.0
+                - (100.0 - self.damage_reduction_percent)
+                    * (100.0 - other.damage_reduction_percent)
+                    / 100.0,
+            absorption: self.absorption.saturating_add(other.absorption),
+        }
+    }
+}
+
+#[inline]
+#[must_use]
+fn percent_of_health(health: i32, percent: f64) -> f64 {
+    f64::from(health) * percent / 100.0
+}
+
+// Buffs a teammate gives to you, based on your maximum Health.
+#[inline]
+#[must_use]
+pub(crate) fn calculate_member_buffs(
+    member: PartyMember,
+    health: i32,
+) -> PartyBuffs {
+    let scale = f64::from(member.class_level.clamp(0, MAXIMUM_CLASS_LEVEL))
+        / f64::from(MAXIMUM_CLASS_LEVEL);
+
+    match member.class {
+        DungeonClass::Healer => PartyBuffs {
+            healing_per_second: percent_of_health(
+                health,
+                (HEALING_AURA_HEAL_PERCENT_PER_SECOND
+                    + HEALING_ORBS_HEAL_PERCENT_PER_SECOND)
+                    * scale,
+            ),
+            damage_reduction_percent: 0.0,
+            absorption: f64_to_i32(
+                percent_of_health(health, WISH_ABSORPTION_PERCENT * scale)
+                    .trunc(),
+            ),
+        },
+
+        DungeonClass::Tank => PartyBuffs {
+            healing_per_second: 0.0,
+            damage_reduction_percent: TANK_DAMAGE_REDUCTION_PERCENT * scale,
+            absorption: f64_to_i32(
+                percent_of_health(health, TANK_ABSORPTION_PERCENT * scale)
+                    .trunc(),
+            ),
+        },
+
+        DungeonClass::Mage | DungeonClass::Berserk | DungeonClass::Archer =>
+            PartyBuffs::default(),
+    }
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn calculate_party_buffs(
+    members: &[PartyMember],
+    health: i32,
+) -> PartyBuffs {
+    members
+        .iter()
+        .map(|&member| calculate_member_buffs(member, health))
+        .fold(PartyBuffs::default(), PartyBuffs::combine)
+}
+
+// Absorption adds to your Health before Defense is applied, and damage
+// reduction makes every point of Effective Health worth more.
+#[inline]
+#[must_use]
+pub(crate) fn calculate_effective_health_with_party_buffs(
+    health: i32,
+    defense: i32,
+    buffs: PartyBuffs,
+) -> i32 {
+    let effective_health = calculate_effective_health(
+        health.saturating_add(buffs.absorption),
+        defense,
+    );
+    let damage_taken_multiplier = 1.0 - buffs.damage_reduction_percent / 100.0;
+
+    if damage_taken_multiplier <= 0.0 {
+        return i32::MAX;
+    }
+
+    f64_to_i32(
+        (f64::from(effective_health) / damage_taken_multiplier)
+            .trunc()
+            .min(f64::from(i32::MAX)),
+    )
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn ask_party() -> Vec<PartyMember> {
+    let teammates = ask_int_input(
+        "How many teammates do you want to include? (enter 0 for none): ",
+        Some(0),
+        Some(MAXIMUM_TEAMMATES),
+    );
+
+    (1..=teammates)
+        .map(|teammate| {
+            println!();
+            println!("Select the class of teammate {teammate}: ");
+
+            for (index, class) in ALL_DUNGEON_CLASSES.iter().enumerate() {
+                println!(
+                    " {}. {class}",
+                    (index + 1).to_string().bright_blue()
+                );
+            }
+
+            let selection = ask_int_input(
+                "Enter a number to select: ",
+                Some(1),
+                Some(usize_to_i32(ALL_DUNGEON_CLASSES.len())),
+            );
+
+            PartyMember {
+                class: usize::try_from(selection - 1)
+                    .ok()
+                    .and_then(|index| ALL_DUNGEON_CLASSES.get(index))
+                    .copied()
+                    .unwrap_or(DungeonClass::Healer),
+                class_level: ask_int_input(
+                    "What's their class level?: ",
+                    Some(0),
+                    Some(MAXIMUM_CLASS_LEVEL),
+                ),
+            }
+        })
+        .collect()
+}
+
+#[inline]
+pub(crate) fn print_party_buffs_note() {
+    println!(
+        "{} Party buffs are approximations of the class passives and abilities at the given class levels, assuming your teammates stay in range and use their abilities on cooldown.",
+        "Note:".red()
+    );
+}
+
+#[inline]
+pub(crate) fn party_buffs_report(
+    start_without_user_input: &mut Option<Instant>,
+) -> bool {
+    let Some(enemies) = enemies() else {
+        return false;
+    };
+
+    // Only the enemies the party buffs matter the most against, on the
+    // floors they are in.
+    let party_enemies = enemies
+        .iter()
+        .filter(|enemy| {
+            enemy.name.starts_with("Shadow Assassin")
+                || enemy.name.starts_with("Fels")
+        })
+        .collect::<Vec<_>>();
+
+    println!();
+    println!("Select your enemy: ");
+
+    for (index, enemy) in party_enemies.iter().enumerate() {
+        println!(" {}. {}", (index + 1).to_string().bright_blue(), enemy.name);
+    }
+
+    let selection = ask_int_input(
+        "Enter a number to select: ",
+        Some(1),
+        Some(usize_to_i32(party_enemies.len())),
+    );
+
+    let Some(enemy) = usize::try_from(selection - 1)
+        .ok()
+        .and_then(|index| party_enemies.get(index))
+    else {
+        eprintln!("{}", "error: no enemies found in the database".red());
+
+        return false;
+    };
+
+    let name = &enemy.name;
+    let selected_enemy = select_attack(enemy);
+    let enemy_damage_per_hit = selected_enemy.damage_per_hit;
+
+    let health = ask_int_input("What's your Health?: ", Some(100), None);
+    let defense = ask_defense(selected_enemy);
+    let crit_damage = ask_int_input(
+        "What's your Crit Damage? (If you don't plan to use Wither Shield, enter 0): ",
+        Some(0),
+        None,
+    );
+
+    let members = ask_party();
+
+    *start_without_user_input = Some(Instant::now());
+
+    // The buffs scale with your max Health, the Wither Shield only adds to
+    // your Effective Health.
+    let total_health =
+        health.saturating_add(calculate_wither_shield_health(crit_damage));
+
+    let hits_to_die_with = |buffs: PartyBuffs| {
+        calculate_hits_to_die(
+            calculate_effective_health_with_party_buffs(
+                total_health,
+                defense,
+                buffs,
+            ),
+            enemy_damage_per_hit,
+        )
+        .trunc()
+    };
+
+    let hits_to_die_without_party = hits_to_die_with(PartyBuffs::default());
+
+    println!();
+    print_party_buffs_note();
+    println!();
+    println!(
+        "{name} deals {} damage per hit. Without party buffs, you take {hits_to_die_without_party} hits to die.",
+        with_comma_separators(&enemy_damage_per_hit.to_string())
+            .unwrap_or_else(|| enemy_damage_per_hit.to_string())
+    );
+
+    for (index, &member) in members.iter().enumerate() {
+        let buffs = calculate_member_buffs(member, health);
+        let extra_hits = hits_to_die_with(buffs) - hits_to_die_without_party;
+
+        println!(
+            " {}. {} (level {}): {} extra hits, {:.2} healing per second",
+            (index + 1).to_string().bright_blue(),
+            member.class,
+            member.class_level,
+            extra_hits.to_string().bright_green(),
+            buffs.healing_per_second
+        );
+    }
+
+    let buffs = calculate_party_buffs(&members, health);
+    let hits_to_die = hits_to_die_with(buffs);
+
+    println!();
+    println!(
+        "With your whole party, you take {} hits to die ({} extra hits), with {} absorption, {:.2}% damage reduction and {:.2} healing per second.",
+        hits_to_die.to_string().bright_green(),
+        hits_to_die - hits_to_die_without_party,
+        buffs.absorption,
+        buffs.damage_reduction_percent,
+        buffs.healing_per_second
+    );
+
+    true
+}
@@ -0,0 +1,214 @@
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use colored::Colorize;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::prices::BazaarProduct;
+use crate::prices::BinListings;
+use crate::utils::nano_time;
+use crate::utils::read_file;
+use crate::utils::write_file_atomically;
+
+pub(crate) const DEFAULT_PRICE_CACHE_TTL_SECONDS: u64 = 5 * 60;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct Cached<T> {
+    pub(crate) value: T,
+    // Seconds since the unix epoch.
+    pub(crate) fetched_at: u64,
+    // The auction category the value was fetched with, so it can be fetched
+    // again the same way.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) category: Option<String>,
+}
+
+impl<T> Cached<T> {
+    #[inline]
+    #[must_use]
+    pub(crate) const fn age(&self, now: u64) -> u64 {
+        now.saturating_sub(self.fetched_at)
+    }
+}
+
+// Cached values by item ID.
+pub(crate) type CachedPrices<T> = HashMap<String, Cached<T>>;
+
+// Shared by every feature that needs prices. The values are None if the item
+// was not for sale when fetched.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct PriceCache {
+    #[serde(default)]
+    lowest_bin: CachedPrices<Option<i64>>,
+    #[serde(default)]
+    bazaar: CachedPrices<Option<BazaarProduct>>,
+
+    #[serde(default)]
+    bin_listings: CachedPrices<BinListings>,
+}
+
+impl PriceCache {
+    #[inline]
+    #[must_use]
+    pub(crate) const fn lowest_bin(&self) -> &CachedPrices<Option<i64>> {
+        &self.lowest_bin
+    }
+
+    #[inline]
+    #[must_use]
+    pub(crate) const fn lowest_bin_mut(
+        &mut self,
+    ) -> &mut CachedPrices<Option<i64>> {
+        &mut self.lowest_bin
+    }
+
+    #[inline]
+    #[must_use]
+    pub(crate) const fn bazaar(&self) -> &CachedPrices<Option<BazaarProduct>> {
+        &self.bazaar
+    }
+
+    #[inline]
+    #[must_use]
+    pub(crate) const fn bazaar_mut(
+        &mut self,
+    ) -> &mut CachedPrices<Option<BazaarProduct>> {
+        &mut self.bazaar
+    }
+
+    #[inline]
+    #[must_use]
+    pub(crate) const fn bin_listings(&self) -> &CachedPrices<BinListings> {
+        &self.bin_listings
+    }
+
+    #[inline]
+    #[must_use]
+    pub(crate) const fn bin_listings_mut(
+        &mut self,
+    ) -> &mut CachedPrices<BinListings> {
+        &mut self.bin_listings
+    }
+}
+
+#[inline]
+pub(crate) fn insert_cached<T>(
+    cached: &mut CachedPrices<T>,
+    ids: &[String],
+    category: Option<&str>,
+    values: Vec<T>,
+    now: u64,
+) {
+    cached.extend(ids.iter().cloned().zip(values.into_iter().map(|value| {
+        Cached {
+            value,
+            fetched_at: now,
+            category: category.map(str::to_owned),
+        }
+    })));
+}
+
+// IDs of every cached value by the category they were fetched with, sorted.
+#[inline]
+#[must_use]
+pub(crate) fn cached_ids_by_category<T>(
+    cached: &CachedPrices<T>,
+) -> BTreeMap<Option<String>, Vec<String>> {
+    let mut ids_by_category = BTreeMap::<_, Vec<_>>::new();
+
+    for (id, cached) in cached {
+        ids_by_category
+            .entry(cached.category.clone())
+            .or_default()
+            .push(id.clone());
+    }
+
+    for ids in ids_by_category.values_mut() {
+        ids.sort_unstable();
+    }
+
+    ids_by_category
+}
+
+#[inline]
+#[must_use]
+fn get_price_cache_file() -> PathBuf {
+    PathBuf::from(Path::new("data")).join("prices.json")
+}
+
+// Returns an empty cache if there is no cache file yet or it can't be read.
+#[inline]
+#[must_use]
+pub(crate) fn load_price_cache() -> PriceCache {
+    let file = get_price_cache_file();
+
+    if !file.exists() {
+        return PriceCache::default();
+    }
+
+    let Some(json) = read_file(&file) else {
+        return PriceCache::default();
+    };
+
+    match serde_json::from_str(&json) {
+        Ok(cache) => cache,
+
+        Err(e) => {
+            eprintln!(
+                "{}{e}",
+                "warning: can't parse the price cache, ignoring it: ".yellow()
+            );
+
+            PriceCache::default()
+        },
+    }
+}
+
+#[inline]
+pub(crate) fn save_price_cache(cache: &PriceCache) -> bool {
+    let file = get_price_cache_file();
+
+    if let Some(folder) = file.parent()
+        && let Err(e) = fs::create_dir_all(folder)
+    {
+        eprintln!("{}{e}", "error: can't create data directory: ".red());
+
+        return false;
+    }
+
+    match serde_json::to_string_pretty(cache) {
+        Ok(json) => write_file_atomically(&file, &json),
+
+        Err(e) => {
+            eprintln!(
+                "{}{e}",
+                "error: can't convert price cache to json: ".red()
+            );
+
+            false
+        },
+    }
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn unix_seconds() -> u64 {
+    nano_time()
+        .and_then(|nanos| u64::try_from(nanos / 1_000_000_000).ok())
+        .unwrap_or(0)
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn format_age(seconds: u64) -> String {
+    match seconds {
+        0..60 => format!("{seconds} seconds"),
+        60..3600 => format!("{} minutes", seconds / 60),
+        3600..86400 => format!("{} hours", seconds / 3600),
+        _ => format!("{} days", seconds / 86400),
+    }
+}
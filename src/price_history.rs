@@ -0,0 +1,458 @@
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use colored::Colorize;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::price_cache::format_age;
+use crate::price_cache::unix_seconds;
+use crate::prices::BazaarProduct;
+use crate::prices::BazaarSide;
+use crate::utils::f64_to_i32;
+use crate::utils::f64_to_i64;
+use crate::utils::format_coins_f64;
+use crate::utils::i64_to_f64;
+use crate::utils::read_file;
+use crate::utils::usize_to_f64;
+use crate::utils::write_file_atomically;
+
+static PRICE_SMOOTHING_WINDOW: OnceLock<Option<u64>> = OnceLock::new();
+
+pub(crate) const DEFAULT_HISTORY_WINDOW_SECONDS: u64 = 7 * 86400;
+// The largest window that can be asked for, older records are dropped.
+pub(crate) const MAXIMUM_HISTORY_WINDOW_SECONDS: u64 = 30 * 86400;
+
+const SPARKLINE_WIDTH: usize = 40;
+const SPARKLINE_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Market {
+    LowestBin,
+    BazaarBuy,
+    BazaarSell,
+}
+
+impl Market {
+    pub(crate) const ALL: [Self; 3] =
+        [Self::LowestBin, Self::BazaarBuy, Self::BazaarSell];
+
+    #[inline]
+    #[must_use]
+    pub(crate) const fn name(self) -> &'static str {
+        match self {
+            Self::LowestBin => "Lowest BIN",
+            Self::BazaarBuy => "Bazaar instant buy",
+            Self::BazaarSell => "Bazaar instant sell",
+        }
+    }
+}
+
+// A price as it was fetched, stored as a line of the history file.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub(crate) struct PriceRecord {
+    pub(crate) id: String,
+    pub(crate) market: Market,
+    pub(crate) price: f64,
+    // Seconds since the unix epoch.
+    pub(crate) recorded_at: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct PriceSummary {
+    pub(crate) min: f64,
+    pub(crate) median: f64,
+    pub(crate) max: f64,
+}
+
+// Uses the median lowest BIN over the window in the calculators instead of
+// the current one, which a single underpriced or overpriced listing can
+// move.
+#[inline]
+pub(crate) fn configure_price_smoothing(window_seconds: Option<u64>) {
+    if PRICE_SMOOTHING_WINDOW.set(window_seconds).is_err() {
+        eprintln!(
+            "{}",
+            "warning: the price smoothing is already configured".yellow()
+        );
+    }
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn price_smoothing_window() -> Option<u64> {
+    *PRICE_SMOOTHING_WINDOW.get_or_init(|| None)
+}
+
+#[inline]
+#[must_use]
+fn get_price_history_file() -> PathBuf {
+    PathBuf::from(Path::new("data")).join("price_history.jsonl")
+}
+
+// Parses a window like 30m, 12h or 7d into seconds, up to the maximum
+// window.
+#[inline]
+#[must_use]
+pub(crate) fn parse_window(window: &str) -> Option<u64> {
+    let window = window.trim();
+    let unit_seconds = match window.chars().last()? {
+        'm' => 60,
+        'h' => 3600,
+        'd' => 86400,
+
+        _ => return None,
+    };
+
+    window
+        .get(..window.len() - 1)?
+        .parse::<u64>()
+        .ok()
+        .filter(|&amount| amount > 0)?
+        .checked_mul(unit_seconds)
+        .filter(|&seconds| seconds <= MAXIMUM_HISTORY_WINDOW_SECONDS)
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn lowest_bin_records(
+    ids: &[String],
+    prices: &[Option<i64>],
+    now: u64,
+) -> Vec<PriceRecord> {
+    ids.iter()
+        .zip(prices)
+        .filter_map(|(id, price)| {
+            Some(PriceRecord {
+                id: id.clone(),
+                market: Market::LowestBin,
+                price: i64_to_f64((*price)?),
+                recorded_at: now,
+            })
+        })
+        .collect()
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn bazaar_records(
+    ids: &[String],
+    products: &[Option<BazaarProduct>],
+    now: u64,
+) -> Vec<PriceRecord> {
+    ids.iter()
+        .zip(products)
+        .filter_map(|(id, product)| Some((id, (*product)?)))
+        .flat_map(|(id, product)| {
+            [
+                (Market::BazaarBuy, BazaarSide::Buy),
+                (Market::BazaarSell, BazaarSide::Sell),
+            ]
+            .into_iter()
+            .filter_map(move |(market, side)| {
+                Some(PriceRecord {
+                    id: id.clone(),
+                    market,
+                    price: product.price(side)?,
+                    recorded_at: now,
+                })
+            })
+        })
+        .collect()
+}
+
+// Appends the records to the history file, only warning on errors since the
+// prices can still be used.
+#[inline]
+pub(crate) fn record_prices(records: &[PriceRecord]) {
+    if records.is_empty() {
+        return;
+    }
+
+    let file = get_price_history_file();
+
+    if let Some(folder) = file.parent()
+        && let Err(e) = fs::create_dir_all(folder)
+    {
+        eprintln!("{}{e}", "warning: can't create data directory: ".yellow());
+
+        return;
+    }
+
+    let mut lines = String::new();
+
+    for record in records {
+        match serde_json::to_string(record) {
+            Ok(json) => {
+                lines.push_str(&json);
+                lines.push('\n');
+            },
+
+            Err(e) => {
+                eprintln!(
+                    "{}{e}",
+                    "warning: can't convert price record to json: ".yellow()
+                );
+
+                return;
+            },
+        }
+    }
+
+    if let Err(e) = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&file)
+        .and_then(|mut history| history.write_all(lines.as_bytes()))
+    {
+        eprintln!(
+            "{}{e}",
+            "warning: can't record prices to the price history: ".yellow()
+        );
+    }
+}
+
+// Skips the lines that can't be parsed, so a line cut off while being
+// written doesn't lose the whole history.
+#[inline]
+#[must_use]
+pub(crate) fn parse_price_history(jsonl: &str) -> Vec<PriceRecord> {
+    jsonl
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+// Returns the recorded prices of the item in the market that are not older
+// than the window, oldest first.
+#[inline]
+#[must_use]
+pub(crate) fn prices_in_window(
+    history: &[PriceRecord],
+    id: &str,
+    market: Market,
+    window_seconds: u64,
+    now: u64,
+) -> Vec<f64> {
+    let mut records = history
+        .iter()
+        .filter(|record| {
+            record.id == id
+                && record.market == market
+                && now.saturating_sub(record.recorded_at) <= window_seconds
+        })
+        .collect::<Vec<_>>();
+
+    records.sort_by_key(|record| record.recorded_at);
+
+    records.into_iter().map(|record| record.price).collect()
+}
+
+// Drops the records older than the maximum window, since no window can use
+// them.
+#[inline]
+#[must_use]
+pub(crate) fn prune_price_history(
+    history: Vec<PriceRecord>,
+    now: u64,
+) -> Vec<PriceRecord> {
+    history
+        .into_iter()
+        .filter(|record| {
+            now.saturating_sub(record.recorded_at)
+                <= MAXIMUM_HISTORY_WINDOW_SECONDS
+        })
+        .collect()
+}
+
+// Loads the history, rewriting the file without the records that are too
+// old so it doesn't keep growing.
+#[inline]
+#[must_use]
+fn load_price_history() -> Vec<PriceRecord> {
+    let file = get_price_history_file();
+
+    if !file.exists() {
+        return vec![];
+    }
+
+    let Some(jsonl) = read_file(&file) else {
+        return vec![];
+    };
+
+    let history = parse_price_history(&jsonl);
+    let recorded = history.len();
+    let history = prune_price_history(history, unix_seconds());
+
+    if history.len() < recorded {
+        let mut lines = String::new();
+
+        for record in &history {
+            if let Ok(json) = serde_json::to_string(record) {
+                lines.push_str(&json);
+                lines.push('\n');
+            }
+        }
+
+        if !write_file_atomically(&file, &lines) {
+            eprintln!(
+                "{}",
+                "warning: can't drop old prices from the price history"
+                    .yellow()
+            );
+        }
+    }
+
+    history
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn summarize_prices(prices: &[f64]) -> Option<PriceSummary> {
+    let mut sorted = prices.to_vec();
+
+    sorted.sort_by(f64::total_cmp);
+
+    let middle = sorted.len() / 2;
+    let median = if sorted.len() % 2 == 0 {
+        f64::midpoint(
+            *sorted.get(middle.checked_sub(1)?)?,
+            *sorted.get(middle)?,
+        )
+    } else {
+        *sorted.get(middle)?
+    };
+
+    Some(PriceSummary { min: *sorted.first()?, median, max: *sorted.last()? })
+}
+
+// Draws the prices with block characters, averaging neighbouring prices if
+// there are more than the width.
+#[inline]
+#[must_use]
+pub(crate) fn sparkline(prices: &[f64], width: usize) -> String {
+    if prices.is_empty() || width == 0 {
+        return String::new();
+    }
+
+    let points = prices
+        .chunks(prices.len().div_ceil(width))
+        .map(|chunk| chunk.iter().sum::<f64>() / usize_to_f64(chunk.len()))
+        .collect::<Vec<_>>();
+
+    let min = points.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = points.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let top_level = SPARKLINE_LEVELS.len() - 1;
+
+    points
+        .into_iter()
+        .map(|point| {
+            let level = if max > min {
+                usize::try_from(f64_to_i32(
+                    ((point - min) / (max - min) * usize_to_f64(top_level))
+                        .round(),
+                ))
+                .unwrap_or(0)
+            } else {
+                top_level / 2
+            };
+
+            SPARKLINE_LEVELS[level.min(top_level)]
+        })
+        .collect()
+}
+
+// Replaces the lowest BIN prices with their median over the smoothing
+// window if it's configured, keeping the current price of items with no
+// recorded prices and items no one is selling.
+#[inline]
+#[must_use]
+pub(crate) fn smooth_lowest_bin_prices(
+    ids: &[String],
+    prices: Vec<Option<i64>>,
+) -> Vec<Option<i64>> {
+    let Some(window_seconds) = price_smoothing_window() else {
+        return prices;
+    };
+
+    let history = load_price_history();
+    let now = unix_seconds();
+
+    println!(
+        "Using the median lowest BIN of the last {}.",
+        format_age(window_seconds).bright_yellow()
+    );
+
+    ids.iter()
+        .zip(prices)
+        .map(|(id, price)| {
+            let price = price?;
+            let recorded = prices_in_window(
+                &history,
+                id,
+                Market::LowestBin,
+                window_seconds,
+                now,
+            );
+
+            Some(
+                summarize_prices(&recorded).map_or(price, |summary| {
+                    f64_to_i64(summary.median.round())
+                }),
+            )
+        })
+        .collect()
+}
+
+// Prints the minimum, median and maximum price of the item in each market
+// it has recorded prices in over the window, with a sparkline of them.
+#[inline]
+pub(crate) fn print_price_history(id: &str, window_seconds: u64) -> bool {
+    let history = load_price_history();
+    let now = unix_seconds();
+
+    println!(
+        "{} over the last {}:",
+        id.bright_blue(),
+        format_age(window_seconds)
+    );
+
+    let mut found = false;
+
+    for market in Market::ALL {
+        let prices =
+            prices_in_window(&history, id, market, window_seconds, now);
+
+        let Some(summary) = summarize_prices(&prices) else {
+            continue;
+        };
+
+        found = true;
+
+        println!(
+            " {} ({} prices): min {}, median {}, max {}",
+            market.name(),
+            prices.len(),
+            format_coins_f64(summary.min).bright_green(),
+            format_coins_f64(summary.median).bright_yellow(),
+            format_coins_f64(summary.max).bright_red()
+        );
+        println!("  {}", sparkline(&prices, SPARKLINE_WIDTH));
+    }
+
+    if !found {
+        eprintln!(
+            "{}",
+            "error: no recorded prices found, prices are recorded when fetched by a calculator or a prices command"
+                .red()
+        );
+    }
+
+    found
+}
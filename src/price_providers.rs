@@ -0,0 +1,964 @@
+use core::cmp;
+use core::time::Duration;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use colored::Colorize;
+use futures::StreamExt;
+use futures::stream::FuturesOrdered;
+use reqwest::Client;
+use reqwest::RequestBuilder;
+use reqwest::tls::Version;
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::time::MissedTickBehavior;
+
+use crate::item_data::decompress_item_data;
+use crate::item_data::find_item_id;
+use crate::prices::BazaarProduct;
+use crate::prices::BinListings;
+use crate::utils::f64_to_i64;
+use crate::utils::read_file;
+
+const SLOTHPIXEL_API_URL: &str = "https://api.slothpixel.me/api/skyblock";
+const SLOTHPIXEL_PAGE_SIZE: usize = 100;
+// Slothpixel allows around a request per second, so the extra pages of
+// listings are fetched one after another at this pace.
+const SLOTHPIXEL_REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+const HYPIXEL_API_URL: &str = "https://api.hypixel.net/v2";
+const COFLNET_API_URL: &str = "https://sky.coflnet.com";
+// Hypixel has every auction on around a hundred pages, which are all
+// downloaded on each poll.
+const HYPIXEL_POLL_INTERVAL_SECONDS: u64 = 300;
+const STATIC_PRICES_FILE: &str = "prices.json";
+
+const USER_AGENT: &str =
+    concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+// A source of auction and Bazaar prices. The fetch methods return the price
+// of each item in the same order as the IDs, None for items that are not for
+// sale, or None for the whole list if the provider can't be used right now.
+pub(crate) trait PriceProvider {
+    fn name(&self) -> &'static str;
+
+    // The category narrows down the search if given, providers that can't
+    // filter by category ignore it.
+    async fn fetch_lowest_bin_prices(
+        &self,
+        ids: &[String],
+        category: Option<&str>,
+    ) -> Option<Vec<Option<i64>>>;
+
+    // Providers that don't have some of the prices or volumes leave them
+    // None.
+    async fn fetch_bazaar_products(
+        &self,
+        ids: &[String],
+    ) -> Option<Vec<Option<BazaarProduct>>>;
+
+    // Fetches at least depth of the cheapest listings if there are that
+    // many, providers that can't page through listings may return less.
+    async fn fetch_bin_listings(
+        &self,
+        ids: &[String],
+        category: Option<&str>,
+        depth: usize,
+    ) -> Option<Vec<BinListings>>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum Provider {
+    Slothpixel(SlothpixelProvider),
+    Hypixel(HypixelProvider),
+    Coflnet(CoflnetProvider),
+    StaticFile(StaticFileProvider),
+}
+
+impl Provider {
+    pub(crate) const NAMES: [&'static str; 4] =
+        ["slothpixel", "hypixel", "coflnet", "file"];
+
+    // The location is the base URL of the API, or the path of the file for
+    // the static file provider. The default one is used if it's None.
+    #[inline]
+    #[must_use]
+    pub(crate) fn from_name(
+        name: &str,
+        location: Option<&str>,
+    ) -> Option<Self> {
+        match name {
+            "slothpixel" => Some(Self::Slothpixel(SlothpixelProvider {
+                base_url: base_url_or_default(location, SLOTHPIXEL_API_URL),
+            })),
+            "hypixel" => Some(Self::Hypixel(HypixelProvider {
+                base_url: base_url_or_default(location, HYPIXEL_API_URL),
+            })),
+            "coflnet" => Some(Self::Coflnet(CoflnetProvider {
+                base_url: base_url_or_default(location, COFLNET_API_URL),
+            })),
+            "file" => Some(Self::StaticFile(StaticFileProvider {
+                path: PathBuf::from(location.unwrap_or(STATIC_PRICES_FILE)),
+            })),
+
+            _ => None,
+        }
+    }
+
+    // The online providers, in the order they are tried by default.
+    #[inline]
+    #[must_use]
+    pub(crate) fn defaults() -> Vec<Self> {
+        ["slothpixel", "hypixel", "coflnet"]
+            .iter()
+            .filter_map(|name| Self::from_name(name, None))
+            .collect()
+    }
+
+    // Seconds to wait between polls of the lowest BIN of the items to stay
+    // under the rate limits, allowing a second per request.
+    #[inline]
+    #[must_use]
+    pub(crate) fn get_poll_interval_seconds(&self, items: usize) -> u64 {
+        match self {
+            Self::Slothpixel(_) | Self::Coflnet(_) =>
+                u64::try_from(items).unwrap_or(u64::MAX),
+            Self::Hypixel(_) => HYPIXEL_POLL_INTERVAL_SECONDS,
+            Self::StaticFile(_) => 0,
+        }
+    }
+}
+
+impl PriceProvider for Provider {
+    #[inline]
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Slothpixel(provider) => provider.name(),
+            Self::Hypixel(provider) => provider.name(),
+            Self::Coflnet(provider) => provider.name(),
+            Self::StaticFile(provider) => provider.name(),
+        }
+    }
+
+    #[inline]
+    async fn fetch_lowest_bin_prices(
+        &self,
+        ids: &[String],
+        category: Option<&str>,
+    ) -> Option<Vec<Option<i64>>> {
+        match self {
+            Self::Slothpixel(provider) =>
+                provider.fetch_lowest_bin_prices(ids, category).await,
+            Self::Hypixel(provider) =>
+                provider.fetch_lowest_bin_prices(ids, category).await,
+            Self::Coflnet(provider) =>
+                provider.fetch_lowest_bin_prices(ids, category).await,
+            Self::StaticFile(provider) =>
+                provider.fetch_lowest_bin_prices(ids, category).await,
+        }
+    }
+
+    #[inline]
+    async fn fetch_bazaar_products(
+        &self,
+        ids: &[String],
+    ) -> Option<Vec<Option<BazaarProduct>>> {
+        match self {
+            Self::Slothpixel(provider) =>
+                provider.fetch_bazaar_products(ids).await,
+            Self::Hypixel(provider) =>
+                provider.fetch_bazaar_products(ids).await,
+            Self::Coflnet(provider) =>
+                provider.fetch_bazaar_products(ids).await,
+            Self::StaticFile(provider) =>
+                provider.fetch_bazaar_products(ids).await,
+        }
+    }
+
+    #[inline]
+    async fn fetch_bin_listings(
+        &self,
+        ids: &[String],
+        category: Option<&str>,
+        depth: usize,
+    ) -> Option<Vec<BinListings>> {
+        match self {
+            Self::Slothpixel(provider) =>
+                provider.fetch_bin_listings(ids, category, depth).await,
+            Self::Hypixel(provider) =>
+                provider.fetch_bin_listings(ids, category, depth).await,
+            Self::Coflnet(provider) =>
+                provider.fetch_bin_listings(ids, category, depth).await,
+            Self::StaticFile(provider) =>
+                provider.fetch_bin_listings(ids, category, depth).await,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct SlothpixelProvider {
+    base_url: String,
+}
+
+impl SlothpixelProvider {
+    #[inline]
+    fn request(&self, client: &Client, path: &str) -> RequestBuilder {
+        client
+            .get(format!("{}/{path}", self.base_url))
+            .header("Accept", "application/json; charset=utf-8")
+            .header("Accept-Encoding", "br")
+    }
+
+    // BIN auctions of the item, the cheapest first. Pages start from 1.
+    #[inline]
+    fn auctions_request(
+        &self,
+        client: &Client,
+        id: &str,
+        category: Option<&str>,
+        limit: usize,
+        page: usize,
+    ) -> RequestBuilder {
+        let request = self.request(client, "auctions").query(&[
+            ("limit", limit.to_string().as_str()),
+            ("page", page.to_string().as_str()),
+            ("sortOrder", "asc"),
+            ("sortBy", "starting_bid"),
+            ("id", id),
+            ("bin", "true"),
+        ]);
+
+        match category {
+            Some(category) => request.query(&[("category", category)]),
+            None => request,
+        }
+    }
+}
+
+impl PriceProvider for SlothpixelProvider {
+    #[inline]
+    fn name(&self) -> &'static str {
+        "Slothpixel"
+    }
+
+    #[inline]
+    async fn fetch_lowest_bin_prices(
+        &self,
+        ids: &[String],
+        category: Option<&str>,
+    ) -> Option<Vec<Option<i64>>> {
+        let client = build_client(&self.base_url, true)?;
+
+        let requests = ids
+            .iter()
+            .map(|id| self.auctions_request(&client, id, category, 1, 1))
+            .collect();
+
+        send_all(requests)
+            .await?
+            .iter()
+            .map(|json| {
+                extract_slothpixel_listings(json)
+                    .map(|listings| listings.prices.first().copied())
+            })
+            .collect()
+    }
+
+    #[inline]
+    async fn fetch_bazaar_products(
+        &self,
+        ids: &[String],
+    ) -> Option<Vec<Option<BazaarProduct>>> {
+        let client = build_client(&self.base_url, true)?;
+
+        let requests = ids
+            .iter()
+            .map(|id| self.request(&client, &format!("bazaar/{id}")))
+            .collect();
+
+        send_all(requests)
+            .await?
+            .iter()
+            .map(|json| extract_bazaar_product(json).map(Some))
+            .collect()
+    }
+
+    #[inline]
+    async fn fetch_bin_listings(
+        &self,
+        ids: &[String],
+        category: Option<&str>,
+        depth: usize,
+    ) -> Option<Vec<BinListings>> {
+        let client = build_client(&self.base_url, true)?;
+        let page_size = depth.clamp(1, SLOTHPIXEL_PAGE_SIZE);
+
+        let mut listings = send_all(
+            ids.iter()
+                .map(|id| {
+                    self.auctions_request(&client, id, category, page_size, 1)
+                })
+                .collect(),
+        )
+        .await?
+        .iter()
+        .map(extract_slothpixel_listings)
+        .collect::<Option<Vec<_>>>()?;
+
+        // The total is known after the first page, so only the pages needed
+        // for the depth are fetched.
+        let more_pages = listings
+            .iter()
+            .enumerate()
+            .flat_map(|(index, listings)| {
+                let pages = cmp::min(depth, listings.total.unwrap_or(depth))
+                    .div_ceil(page_size);
+
+                (2..=pages).map(move |page| (index, page))
+            })
+            .collect::<Vec<_>>();
+
+        if !more_pages.is_empty() {
+            println!(
+                "Fetching {} more pages of listings, a second apart to respect the rate limits...",
+                more_pages.len()
+            );
+        }
+
+        let responses = send_paced(
+            more_pages
+                .iter()
+                .map(|&(index, page)| {
+                    self.auctions_request(
+                        &client,
+                        &ids[index],
+                        category,
+                        page_size,
+                        page,
+                    )
+                })
+                .collect(),
+            SLOTHPIXEL_REQUEST_INTERVAL,
+        )
+        .await?;
+
+        for (&(index, _), json) in more_pages.iter().zip(&responses) {
+            listings[index]
+                .prices
+                .extend(extract_slothpixel_listings(json)?.prices);
+        }
+
+        for listings in &mut listings {
+            listings.prices.sort_unstable();
+        }
+
+        Some(listings)
+    }
+}
+
+// The official API, it has no search so every auction page is fetched and
+// the item IDs are read from the item data of the auctions.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct HypixelProvider {
+    base_url: String,
+}
+
+impl HypixelProvider {
+    // Prices of every BIN listing of each item, ascending.
+    #[inline]
+    async fn fetch_listing_prices(
+        &self,
+        ids: &[String],
+        category: Option<&str>,
+    ) -> Option<Vec<Vec<i64>>> {
+        let client = build_client(&self.base_url, false)?;
+        let url = format!("{}/skyblock/auctions", self.base_url);
+
+        let first_page =
+            send_all(vec![client.get(&url).query(&[("page", "0")])])
+                .await?
+                .pop()?;
+
+        let Some(total_pages) =
+            first_page.get("totalPages").and_then(Value::as_i64)
+        else {
+            eprintln!(
+                "{}",
+                "error: can't find totalPages field in auctions JSON".red()
+            );
+
+            return None;
+        };
+
+        let mut pages = vec![first_page];
+
+        pages.extend(
+            send_all(
+                (1..total_pages)
+                    .map(|page| {
+                        client.get(&url).query(&[("page", page.to_string())])
+                    })
+                    .collect(),
+            )
+            .await?,
+        );
+
+        let Some(auctions) = pages
+            .iter()
+            .map(|page| page.get("auctions").and_then(Value::as_array))
+            .collect::<Option<Vec<_>>>()
+        else {
+            eprintln!(
+                "{}",
+                "error: can't find auctions field in auctions JSON".red()
+            );
+
+            return None;
+        };
+
+        let mut prices = vec![vec![]; ids.len()];
+
+        for auction in auctions.into_iter().flatten() {
+            if auction.get("bin").and_then(Value::as_bool) != Some(true)
+                || category.is_some_and(|category| {
+                    auction.get("category").and_then(Value::as_str)
+                        != Some(category)
+                })
+            {
+                continue;
+            }
+
+            let (Some(price), Some(id)) = (
+                auction.get("starting_bid").and_then(Value::as_i64),
+                auction
+                    .get("item_bytes")
+                    .and_then(Value::as_str)
+                    .and_then(decompress_item_data)
+                    .and_then(|nbt| find_item_id(&nbt)),
+            ) else {
+                continue;
+            };
+
+            if let Some(index) = ids.iter().position(|wanted| *wanted == id) {
+                prices[index].push(price);
+            }
+        }
+
+        for prices in &mut prices {
+            prices.sort_unstable();
+        }
+
+        Some(prices)
+    }
+}
+
+impl PriceProvider for HypixelProvider {
+    #[inline]
+    fn name(&self) -> &'static str {
+        "Hypixel"
+    }
+
+    #[inline]
+    async fn fetch_lowest_bin_prices(
+        &self,
+        ids: &[String],
+        category: Option<&str>,
+    ) -> Option<Vec<Option<i64>>> {
+        Some(
+            self.fetch_listing_prices(ids, category)
+                .await?
+                .into_iter()
+                .map(|prices| prices.first().copied())
+                .collect(),
+        )
+    }
+
+    #[inline]
+    async fn fetch_bazaar_products(
+        &self,
+        ids: &[String],
+    ) -> Option<Vec<Option<BazaarProduct>>> {
+        let client = build_client(&self.base_url, false)?;
+
+        let json = send_all(vec![
+            client.get(format!("{}/skyblock/bazaar", self.base_url)),
+        ])
+        .await?
+        .pop()?;
+
+        let Some(products) = json.get("products") else {
+            eprintln!(
+                "{}{json}",
+                "error: can't find products field in JSON: ".red()
+            );
+
+            return None;
+        };
+
+        // Products that are not in the Bazaar are None, but a product that
+        // can't be read fails the whole list.
+        ids.iter()
+            .map(|id| {
+                products.get(id).map_or(Some(None), |product| {
+                    extract_bazaar_product(product).map(Some)
+                })
+            })
+            .collect()
+    }
+
+    // Every listing is fetched anyway, so the depth doesn't matter.
+    #[inline]
+    async fn fetch_bin_listings(
+        &self,
+        ids: &[String],
+        category: Option<&str>,
+        _depth: usize,
+    ) -> Option<Vec<BinListings>> {
+        Some(
+            self.fetch_listing_prices(ids, category)
+                .await?
+                .into_iter()
+                .map(|prices| BinListings {
+                    total: Some(prices.len()),
+                    prices,
+                })
+                .collect(),
+        )
+    }
+}
+
+// Compatible with the item price endpoints of Coflnet (sky.coflnet.com).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CoflnetProvider {
+    base_url: String,
+}
+
+impl CoflnetProvider {
+    #[inline]
+    async fn fetch_item_prices(
+        &self,
+        ids: &[String],
+        endpoint: &str,
+    ) -> Option<Vec<Value>> {
+        let client = build_client(&self.base_url, false)?;
+
+        let requests = ids
+            .iter()
+            .map(|id| {
+                client.get(format!(
+                    "{}/api/item/price/{id}/{endpoint}",
+                    self.base_url
+                ))
+            })
+            .collect();
+
+        send_all(requests).await
+    }
+}
+
+impl PriceProvider for CoflnetProvider {
+    #[inline]
+    fn name(&self) -> &'static str {
+        "Coflnet"
+    }
+
+    #[inline]
+    async fn fetch_lowest_bin_prices(
+        &self,
+        ids: &[String],
+        _category: Option<&str>,
+    ) -> Option<Vec<Option<i64>>> {
+        Some(
+            self.fetch_item_prices(ids, "bin")
+                .await?
+                .iter()
+                .map(|json| extract_price(json, "lowest").map(f64_to_coins))
+                .collect(),
+        )
+    }
+
+    // Only the instant prices are available. Items without either price
+    // are not in the Bazaar.
+    #[inline]
+    async fn fetch_bazaar_products(
+        &self,
+        ids: &[String],
+    ) -> Option<Vec<Option<BazaarProduct>>> {
+        Some(
+            self.fetch_item_prices(ids, "current")
+                .await?
+                .iter()
+                .map(|json| {
+                    let buy_price = extract_price(json, "buy");
+                    let sell_price = extract_price(json, "sell");
+
+                    (buy_price.is_some() || sell_price.is_some()).then(|| {
+                        BazaarProduct {
+                            buy: buy_price,
+                            sell: sell_price,
+
+                            ..BazaarProduct::default()
+                        }
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    // Only the cheapest few active listings are available, regardless of the
+    // depth.
+    #[inline]
+    async fn fetch_bin_listings(
+        &self,
+        ids: &[String],
+        _category: Option<&str>,
+        _depth: usize,
+    ) -> Option<Vec<BinListings>> {
+        let client = build_client(&self.base_url, false)?;
+
+        let requests = ids
+            .iter()
+            .map(|id| {
+                client.get(format!(
+                    "{}/api/auctions/tag/{id}/active/bin",
+                    self.base_url
+                ))
+            })
+            .collect();
+
+        Some(
+            send_all(requests)
+                .await?
+                .iter()
+                .map(|json| {
+                    let mut prices = json
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|auction| {
+                            auction.get("startingBid").and_then(Value::as_i64)
+                        })
+                        .collect::<Vec<_>>();
+
+                    prices.sort_unstable();
+
+                    BinListings { total: None, prices }
+                })
+                .collect(),
+        )
+    }
+}
+
+// Reads the prices from a JSON file instead of an API, i.e.
+// {"lowest_bin": {"ITEM_ID": 100}, "bazaar": {"ITEM_ID": {"buy": 2.5,
+// "sell": 2.0}}, "bin_listings": {"ITEM_ID": [100, 120]}}, the Bazaar
+// products can have every field of BazaarProduct.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct StaticFileProvider {
+    path: PathBuf,
+}
+
+#[derive(Debug, Default, Deserialize, Clone, PartialEq)]
+pub(crate) struct StaticPrices {
+    #[serde(default)]
+    pub(crate) lowest_bin: HashMap<String, i64>,
+    #[serde(default)]
+    pub(crate) bazaar: HashMap<String, BazaarProduct>,
+    // The lowest BIN is used as the only listing for items not in here.
+    #[serde(default)]
+    pub(crate) bin_listings: HashMap<String, Vec<i64>>,
+}
+
+impl StaticFileProvider {
+    #[inline]
+    #[must_use]
+    fn read_prices(&self) -> Option<StaticPrices> {
+        parse_static_prices(&self.path, &read_file(&self.path)?)
+    }
+}
+
+impl PriceProvider for StaticFileProvider {
+    #[inline]
+    fn name(&self) -> &'static str {
+        "static price file"
+    }
+
+    #[inline]
+    async fn fetch_lowest_bin_prices(
+        &self,
+        ids: &[String],
+        _category: Option<&str>,
+    ) -> Option<Vec<Option<i64>>> {
+        let prices = self.read_prices()?;
+
+        Some(ids.iter().map(|id| prices.lowest_bin.get(id).copied()).collect())
+    }
+
+    #[inline]
+    async fn fetch_bazaar_products(
+        &self,
+        ids: &[String],
+    ) -> Option<Vec<Option<BazaarProduct>>> {
+        let prices = self.read_prices()?;
+
+        Some(ids.iter().map(|id| prices.bazaar.get(id).copied()).collect())
+    }
+
+    #[inline]
+    async fn fetch_bin_listings(
+        &self,
+        ids: &[String],
+        _category: Option<&str>,
+        _depth: usize,
+    ) -> Option<Vec<BinListings>> {
+        let prices = self.read_prices()?;
+
+        Some(
+            ids.iter()
+                .map(|id| {
+                    let mut prices = prices.bin_listings.get(id).map_or_else(
+                        || {
+                            prices
+                                .lowest_bin
+                                .get(id)
+                                .copied()
+                                .into_iter()
+                                .collect()
+                        },
+                        Clone::clone,
+                    );
+
+                    prices.sort_unstable();
+
+                    BinListings { total: Some(prices.len()), prices }
+                })
+                .collect(),
+        )
+    }
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn parse_static_prices(
+    path: &Path,
+    text: &str,
+) -> Option<StaticPrices> {
+    match serde_json::from_str(text) {
+        Ok(prices) => Some(prices),
+
+        Err(e) => {
+            eprintln!(
+                "{}{}: {e}",
+                "error: can't parse price file: ".red(),
+                path.to_string_lossy()
+            );
+
+            None
+        },
+    }
+}
+
+#[inline]
+#[must_use]
+fn base_url_or_default(base_url: Option<&str>, default: &str) -> String {
+    base_url.unwrap_or(default).trim_end_matches('/').to_owned()
+}
+
+#[inline]
+#[must_use]
+fn f64_to_coins(price: f64) -> i64 {
+    f64_to_i64(price.round())
+}
+
+#[inline]
+#[must_use]
+fn build_client(base_url: &str, http3: bool) -> Option<Client> {
+    let mut builder = reqwest::ClientBuilder::new()
+        .timeout(Duration::from_secs(10))
+        .user_agent(USER_AGENT)
+        .brotli(true);
+
+    // Plain HTTP is only allowed so that a local stand-in API can be used.
+    if base_url.starts_with("https://") {
+        builder = builder.https_only(true).min_tls_version(Version::TLS_1_3);
+
+        if http3 {
+            builder = builder.http3_prior_knowledge();
+        }
+    }
+
+    match builder.build() {
+        Ok(client) => Some(client),
+
+        Err(e) => {
+            eprintln!("{}{e}", "Error when building http client: ".red());
+
+            None
+        },
+    }
+}
+
+// Sends the requests concurrently and returns the JSON responses in the same
+// order, or None if any of them failed.
+#[inline]
+async fn send_all(requests: Vec<RequestBuilder>) -> Option<Vec<Value>> {
+    let mut completion_stream = requests
+        .into_iter()
+        .map(|request| tokio::spawn(request.send()))
+        .collect::<FuturesOrdered<_>>();
+
+    let mut responses = Vec::new();
+
+    while let Some(result_of_task) = completion_stream.next().await {
+        let result_of_request = match result_of_task {
+            Ok(result_of_request) => result_of_request,
+
+            Err(e) => {
+                eprintln!("{}{e}", "Error on task execution: ".red());
+
+                return None;
+            },
+        };
+
+        // Error statuses like rate limits can still have a JSON body, which
+        // must not be read as a reply.
+        let response = match result_of_request
+            .and_then(reqwest::Response::error_for_status)
+        {
+            Ok(response) => response,
+
+            Err(e) => {
+                eprintln!("{}{e}", "Error when getting response: ".red());
+
+                return None;
+            },
+        };
+
+        let response_body = match response.text().await {
+            Ok(response_body) => response_body,
+
+            Err(e) => {
+                eprintln!("{}{e}", "Error when getting response body: ".red());
+
+                return None;
+            },
+        };
+
+        match serde_json::from_str::<Value>(&response_body) {
+            Ok(json) => responses.push(json),
+
+            Err(e) => {
+                eprintln!(
+                    "{}{e}: {response_body}",
+                    "Error when parsing JSON: ".red()
+                );
+
+                return None;
+            },
+        }
+    }
+
+    Some(responses)
+}
+
+// Sends the requests one after another, starting each at least the interval
+// after the one before, and returns the JSON responses in the same order, or
+// None if any of them failed.
+#[inline]
+async fn send_paced(
+    requests: Vec<RequestBuilder>,
+    interval: Duration,
+) -> Option<Vec<Value>> {
+    let mut ticks = tokio::time::interval(interval);
+    let mut responses = Vec::with_capacity(requests.len());
+
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    for request in requests {
+        let _ = ticks.tick().await;
+        responses.extend(send_all(vec![request]).await?);
+    }
+
+    Some(responses)
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn extract_slothpixel_listings(
+    json: &Value,
+) -> Option<BinListings> {
+    let Some(total) = json
+        .get("matching_query")
+        .and_then(Value::as_u64)
+        .and_then(|total| usize::try_from(total).ok())
+    else {
+        eprintln!(
+            "{}{json}",
+            "error: can't find matching_query field in JSON: ".red()
+        );
+
+        return None;
+    };
+
+    let Some(auctions) = json.get("auctions").and_then(Value::as_array) else {
+        eprintln!(
+            "{}{json}",
+            "error: can't find auctions field in JSON: ".red()
+        );
+
+        return None;
+    };
+
+    Some(BinListings {
+        prices: auctions
+            .iter()
+            .filter_map(|auction| {
+                auction.get("starting_bid").and_then(Value::as_i64)
+            })
+            .collect(),
+        total: Some(total),
+    })
+}
+
+// Both Slothpixel and the official API have the instant prices and volumes
+// of a product in its quick_status field, and the top orders in its
+// sell_summary (buy orders) and buy_summary (sell offers) fields.
+#[inline]
+#[must_use]
+pub(crate) fn extract_bazaar_product(json: &Value) -> Option<BazaarProduct> {
+    let Some(quick_status) = json.get("quick_status") else {
+        eprintln!(
+            "{}{json}",
+            "error: can't find quick_status field in JSON: ".red()
+        );
+
+        return None;
+    };
+
+    let top_order = |summary| {
+        json.get(summary)
+            .and_then(|orders| orders.get(0))
+            .and_then(|order| extract_price(order, "pricePerUnit"))
+    };
+
+    let volume = |field| quick_status.get(field).and_then(Value::as_i64);
+
+    Some(BazaarProduct {
+        buy: extract_price(quick_status, "buyPrice"),
+        sell: extract_price(quick_status, "sellPrice"),
+        buy_order: top_order("sell_summary"),
+        sell_offer: top_order("buy_summary"),
+
+        buy_volume: volume("buyVolume"),
+        sell_volume: volume("sellVolume"),
+        buy_moving_week: volume("buyMovingWeek"),
+        sell_moving_week: volume("sellMovingWeek"),
+    })
+}
+
+#[inline]
+#[must_use]
+fn extract_price(json: &Value, field: &str) -> Option<f64> {
+    // A price of 0 means there are no offers.
+    json.get(field).and_then(Value::as_f64).filter(|&price| price > 0.0)
+}
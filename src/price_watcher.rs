@@ -0,0 +1,236 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use colored::Colorize;
+use notify_rust::Notification;
+use notify_rust::Urgency;
+use serde::Deserialize;
+
+use crate::prices::fetch_latest_lowest_bin_prices;
+use crate::prices::get_providers_poll_interval_seconds;
+use crate::prices::is_offline;
+use crate::utils::format_coins;
+use crate::utils::i64_to_f64;
+use crate::utils::read_file;
+
+// Polling more often than this is pointless since the price APIs update
+// their prices about once a minute.
+pub(crate) const MINIMUM_POLL_INTERVAL_SECONDS: u64 = 60;
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub(crate) struct WatchConfig {
+    #[serde(default = "default_poll_interval_seconds")]
+    pub(crate) poll_interval_seconds: u64,
+    pub(crate) items: Vec<WatchedItem>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+pub(crate) struct WatchedItem {
+    pub(crate) id: String,
+    // Alerts when the lowest BIN drops below this price.
+    #[serde(default)]
+    pub(crate) below: Option<i64>,
+    // Alerts when the lowest BIN moves this many percent up or down since the
+    // last alert, or since the watch started.
+    #[serde(default)]
+    pub(crate) change_percent: Option<f64>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct WatchState {
+    last_price: Option<i64>,
+    reference_price: Option<i64>,
+}
+
+#[inline]
+#[must_use]
+const fn default_poll_interval_seconds() -> u64 {
+    MINIMUM_POLL_INTERVAL_SECONDS
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn get_default_watchlist_file() -> PathBuf {
+    PathBuf::from(Path::new("data")).join("watchlist.json")
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn parse_watch_config(json: &str) -> Option<WatchConfig> {
+    match serde_json::from_str::<WatchConfig>(json) {
+        Ok(mut config) => {
+            for item in &mut config.items {
+                item.id = item.id.to_uppercase();
+            }
+
+            Some(config)
+        },
+
+        Err(e) => {
+            eprintln!("{}{e}", "error: can't parse the watchlist: ".red());
+
+            None
+        },
+    }
+}
+
+// The configured interval, raised to the interval the price providers need
+// to stay under their rate limits.
+#[inline]
+#[must_use]
+pub(crate) fn get_poll_interval_seconds(
+    config: &WatchConfig,
+    providers_interval_seconds: u64,
+) -> u64 {
+    config
+        .poll_interval_seconds
+        .max(MINIMUM_POLL_INTERVAL_SECONDS)
+        .max(providers_interval_seconds)
+}
+
+// Updates the state with the new price and returns the alerts to send.
+#[inline]
+#[must_use]
+pub(crate) fn check_price(
+    item: &WatchedItem,
+    state: &mut WatchState,
+    price: i64,
+) -> Vec<String> {
+    let mut alerts = vec![];
+
+    if let Some(threshold) = item.below
+        && price < threshold
+        && state.last_price.is_none_or(|last_price| last_price >= threshold)
+    {
+        alerts.push(format!(
+            "{} dropped below {} coins, the lowest BIN is {} coins",
+            item.id,
+            format_coins(threshold),
+            format_coins(price)
+        ));
+    }
+
+    if let Some(change_percent) = item.change_percent {
+        match state.reference_price {
+            Some(reference_price) if reference_price > 0 => {
+                let change = (i64_to_f64(price) - i64_to_f64(reference_price))
+                    / i64_to_f64(reference_price)
+                    * 100.0;
+
+                if change.abs() >= change_percent {
+                    alerts.push(format!(
+                        "{} moved {change:+.1}% from {} to {} coins",
+                        item.id,
+                        format_coins(reference_price),
+                        format_coins(price)
+                    ));
+
+                    state.reference_price = Some(price);
+                }
+            },
+
+            _ => state.reference_price = Some(price),
+        }
+    }
+
+    state.last_price = Some(price);
+
+    alerts
+}
+
+// Polls the lowest BIN of the items in the watchlist and sends a desktop
+// notification when one of them crosses its threshold or moves too much.
+// Runs until the program is stopped, only returning on errors.
+#[inline]
+pub(crate) async fn watch_prices(watchlist_file: Option<&Path>) -> bool {
+    if is_offline() {
+        eprintln!("{}", "error: can't watch prices in offline mode".red());
+
+        return false;
+    }
+
+    let file = watchlist_file
+        .map_or_else(get_default_watchlist_file, Path::to_path_buf);
+
+    let Some(json) = read_file(&file) else {
+        eprintln!(
+            "{}{}",
+            "error: can't read the watchlist from ".red(),
+            file.display()
+        );
+
+        return false;
+    };
+
+    let Some(config) = parse_watch_config(&json) else {
+        return false;
+    };
+
+    if config.items.is_empty() {
+        eprintln!("{}", "error: the watchlist has no items".red());
+
+        return false;
+    }
+
+    let interval = get_poll_interval_seconds(
+        &config,
+        get_providers_poll_interval_seconds(config.items.len()),
+    );
+
+    if interval != config.poll_interval_seconds {
+        eprintln!(
+            "{}",
+            format!(
+                "warning: polling every {interval} seconds instead to respect the rate limits of the price APIs"
+            )
+            .yellow()
+        );
+    }
+
+    let ids =
+        config.items.iter().map(|item| item.id.clone()).collect::<Vec<_>>();
+    let mut states = vec![WatchState::default(); config.items.len()];
+
+    println!(
+        "Watching the lowest BIN of {} items every {interval} seconds.",
+        ids.len()
+    );
+
+    loop {
+        if let Some(prices) = fetch_latest_lowest_bin_prices(&ids).await {
+            for ((item, state), price) in
+                config.items.iter().zip(&mut states).zip(prices)
+            {
+                let Some(price) = price else {
+                    continue;
+                };
+
+                for alert in check_price(item, state, price) {
+                    println!("{}", alert.bright_green());
+                    notify_price_alert(&alert);
+                }
+            }
+        } else {
+            eprintln!(
+                "{}",
+                "warning: can't fetch prices, retrying on the next poll"
+                    .yellow()
+            );
+        }
+
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+    }
+}
+
+#[inline]
+fn notify_price_alert(alert: &str) {
+    if let Err(e) = Notification::new()
+        .summary("Price alert")
+        .body(alert)
+        .urgency(Urgency::Normal)
+        .show()
+    {
+        eprintln!("{}{e}", "error: can't send desktop notification: ".red());
+    }
+}
@@ -0,0 +1,661 @@
+use core::cmp;
+use core::iter;
+
+use std::sync::OnceLock;
+
+use colored::Colorize;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::price_cache::CachedPrices;
+use crate::price_cache::DEFAULT_PRICE_CACHE_TTL_SECONDS;
+use crate::price_cache::PriceCache;
+use crate::price_cache::cached_ids_by_category;
+use crate::price_cache::format_age;
+use crate::price_cache::insert_cached;
+use crate::price_cache::load_price_cache;
+use crate::price_cache::save_price_cache;
+use crate::price_cache::unix_seconds;
+use crate::price_history::bazaar_records;
+use crate::price_history::lowest_bin_records;
+use crate::price_history::record_prices;
+use crate::price_history::smooth_lowest_bin_prices;
+use crate::price_providers::PriceProvider;
+use crate::price_providers::Provider;
+use crate::utils::ask_float_input;
+use crate::utils::ask_int_input;
+use crate::utils::with_comma_separators;
+
+static PRICE_PROVIDERS: OnceLock<Vec<Provider>> = OnceLock::new();
+static PRICE_CACHE_SETTINGS: OnceLock<PriceCacheSettings> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum BazaarSide {
+    // The price to instantly buy, paid to the sell offers.
+    Buy,
+    // The price to instantly sell, paid by the buy orders.
+    Sell,
+    // The price of a buy order matching the highest one, cheaper than
+    // buying instantly but takes time to fill.
+    BuyOrder,
+    // The price of a sell offer matching the lowest one, more than selling
+    // instantly but takes time to fill.
+    SellOffer,
+}
+
+impl BazaarSide {
+    #[inline]
+    #[must_use]
+    pub(crate) const fn name(self) -> &'static str {
+        match self {
+            Self::Buy => "Instant buy",
+            Self::Sell => "Instant sell",
+            Self::BuyOrder => "Buy order",
+            Self::SellOffer => "Sell offer",
+        }
+    }
+}
+
+// Prices and volumes of a Bazaar product. Prices are None if there are no
+// orders on that side, and anything the provider doesn't have is None.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub(crate) struct BazaarProduct {
+    pub(crate) buy: Option<f64>,
+    pub(crate) sell: Option<f64>,
+    // The highest buy order.
+    pub(crate) buy_order: Option<f64>,
+    // The lowest sell offer.
+    pub(crate) sell_offer: Option<f64>,
+
+    // Items in the sell offers, that can be bought instantly.
+    pub(crate) buy_volume: Option<i64>,
+    // Items in the buy orders, that can be sold instantly.
+    pub(crate) sell_volume: Option<i64>,
+    // Items bought and sold instantly in the last 7 days.
+    pub(crate) buy_moving_week: Option<i64>,
+    pub(crate) sell_moving_week: Option<i64>,
+}
+
+impl BazaarProduct {
+    // Uses the instant price of the other side if the top orders are not
+    // known, since they are about the same.
+    #[inline]
+    #[must_use]
+    pub(crate) fn price(&self, side: BazaarSide) -> Option<f64> {
+        match side {
+            BazaarSide::Buy => self.buy,
+            BazaarSide::Sell => self.sell,
+            BazaarSide::BuyOrder => self.buy_order.or(self.sell),
+            BazaarSide::SellOffer => self.sell_offer.or(self.buy),
+        }
+    }
+}
+
+// Asks whether to buy or sell instantly or with an order.
+#[inline]
+#[must_use]
+pub(crate) fn ask_bazaar_side(buying: bool) -> BazaarSide {
+    let (instant, order) = if buying {
+        (BazaarSide::Buy, BazaarSide::BuyOrder)
+    } else {
+        (BazaarSide::Sell, BazaarSide::SellOffer)
+    };
+
+    println!(
+        "How do you want to {} on the Bazaar?",
+        if buying { "buy" } else { "sell" }
+    );
+    println!(" {}. {}", "1".bright_blue(), instant.name());
+    println!(" {}. {}", "2".bright_blue(), order.name());
+
+    if ask_int_input("Enter a number to select: ", Some(1), Some(2)) == 1 {
+        instant
+    } else {
+        order
+    }
+}
+
+// Tries the given provider first, falling back to the other online
+// providers with their default URLs if it can't be used.
+#[inline]
+pub(crate) fn select_price_provider(provider: Provider) {
+    let name = provider.name();
+
+    let fallbacks = Provider::defaults()
+        .into_iter()
+        .filter(|fallback| fallback.name() != name);
+
+    let providers = iter::once(provider).chain(fallbacks).collect();
+
+    if PRICE_PROVIDERS.set(providers).is_err() {
+        eprintln!(
+            "{}",
+            "warning: the price provider is already selected".yellow()
+        );
+    }
+}
+
+// BIN listings of an item, each listing is assumed to be a single item.
+#[derive(
+    Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq, Hash,
+)]
+pub(crate) struct BinListings {
+    // Prices of the cheapest listings that were fetched, ascending.
+    pub(crate) prices: Vec<i64>,
+    // Listings in total, can be more than the amount of prices fetched. None
+    // if the provider only has the cheapest few and doesn't know the total.
+    pub(crate) total: Option<usize>,
+}
+
+impl BinListings {
+    // Walks the listings from the cheapest, returning the total cost of
+    // buying the amount and the price of the most expensive one bought, or
+    // None if there are not enough listings.
+    #[inline]
+    #[must_use]
+    pub(crate) fn cost_to_buy(&self, amount: usize) -> Option<(i64, i64)> {
+        if amount == 0 {
+            return Some((0, 0));
+        }
+
+        let bought = self.prices.get(..amount)?;
+
+        Some((bought.iter().sum(), *bought.last()?))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct PriceCacheSettings {
+    // Cached prices older than this are fetched again.
+    pub(crate) ttl_seconds: u64,
+    // Only use cached prices, never fetching them.
+    pub(crate) offline: bool,
+}
+
+impl Default for PriceCacheSettings {
+    #[inline]
+    fn default() -> Self {
+        Self { ttl_seconds: DEFAULT_PRICE_CACHE_TTL_SECONDS, offline: false }
+    }
+}
+
+#[inline]
+pub(crate) fn configure_price_cache(settings: PriceCacheSettings) {
+    if PRICE_CACHE_SETTINGS.set(settings).is_err() {
+        eprintln!(
+            "{}",
+            "warning: the price cache is already configured".yellow()
+        );
+    }
+}
+
+#[inline]
+#[must_use]
+fn price_cache_settings() -> PriceCacheSettings {
+    *PRICE_CACHE_SETTINGS.get_or_init(PriceCacheSettings::default)
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn is_offline() -> bool {
+    price_cache_settings().offline
+}
+
+#[inline]
+#[must_use]
+fn price_providers() -> &'static [Provider] {
+    PRICE_PROVIDERS.get_or_init(Provider::defaults)
+}
+
+// The poll interval the selected providers need for the items, the longest
+// of them since any of them is used when the ones before it fail.
+#[inline]
+#[must_use]
+pub(crate) fn get_providers_poll_interval_seconds(items: usize) -> u64 {
+    price_providers()
+        .iter()
+        .map(|provider| provider.get_poll_interval_seconds(items))
+        .max()
+        .unwrap_or(0)
+}
+
+#[inline]
+fn print_provider_failed(provider: &Provider) {
+    eprintln!(
+        "{}{}",
+        "warning: can't get prices from ".yellow(),
+        provider.name()
+    );
+}
+
+// Returns the lowest BIN price of each item in the same order, None for items
+// no one is selling. Returns None if the prices can't be fetched or found in
+// the cache. The category narrows down the search if given.
+#[inline]
+pub(crate) async fn fetch_lowest_bin_prices(
+    ids: &[String],
+    category: Option<&str>,
+) -> Option<Vec<Option<i64>>> {
+    let prices = fetch_with_cache(
+        ids,
+        category,
+        PriceCache::lowest_bin_mut,
+        |_| true,
+        async |ids| fetch_and_record_lowest_bin_prices(ids, category).await,
+    )
+    .await?;
+
+    Some(smooth_lowest_bin_prices(ids, prices))
+}
+
+// Like fetch_lowest_bin_prices, but always fetches the current prices
+// regardless of the cache and the smoothing, saving them to the cache for the
+// other features.
+#[inline]
+pub(crate) async fn fetch_latest_lowest_bin_prices(
+    ids: &[String],
+) -> Option<Vec<Option<i64>>> {
+    let prices = fetch_and_record_lowest_bin_prices(ids, None).await?;
+
+    let mut cache = load_price_cache();
+
+    insert_cached(
+        cache.lowest_bin_mut(),
+        ids,
+        None,
+        prices.clone(),
+        unix_seconds(),
+    );
+
+    if !save_price_cache(&cache) {
+        eprintln!(
+            "{}",
+            "warning: can't save the price cache, look above for possible errors"
+                .yellow()
+        );
+    }
+
+    Some(prices)
+}
+
+// Returns the prices and volumes of each Bazaar product in the same order,
+// None for products that don't exist. Returns None if the products can't be
+// fetched or found in the cache.
+#[inline]
+pub(crate) async fn fetch_bazaar_products(
+    ids: &[String],
+) -> Option<Vec<Option<BazaarProduct>>> {
+    fetch_with_cache(
+        ids,
+        None,
+        PriceCache::bazaar_mut,
+        |_| true,
+        fetch_and_record_bazaar_products,
+    )
+    .await
+}
+
+// Returns the price of each Bazaar product at the side in the same order,
+// None for products with no orders on that side.
+#[inline]
+pub(crate) async fn fetch_bazaar_prices(
+    ids: &[String],
+    side: BazaarSide,
+) -> Option<Vec<Option<f64>>> {
+    Some(
+        fetch_bazaar_products(ids)
+            .await?
+            .into_iter()
+            .map(|product| product?.price(side))
+            .collect(),
+    )
+}
+
+// Returns the BIN listings of each item in the same order, the cheapest
+// first. At least depth listings are fetched if there are that many, so the
+// cost of buying that many can be calculated. Returns None if the listings
+// can't be fetched or found in the cache.
+#[inline]
+pub(crate) async fn fetch_bin_listings(
+    ids: &[String],
+    category: Option<&str>,
+    depth: usize,
+) -> Option<Vec<BinListings>> {
+    fetch_with_cache(
+        ids,
+        category,
+        PriceCache::bin_listings_mut,
+        |listings| {
+            listings.prices.len()
+                >= cmp::min(depth, listings.total.unwrap_or(depth))
+        },
+        async |ids| fetch_and_record_bin_listings(ids, category, depth).await,
+    )
+    .await
+}
+
+// Uses the cached values that are not older than the TTL and fetches the
+// rest. If fetching fails or in offline mode, older cached values are used,
+// showing their age. Cached values that are not complete are fetched again.
+// The category is saved with the fetched values so they can be refreshed the
+// same way.
+#[inline]
+async fn fetch_with_cache<T: Clone>(
+    ids: &[String],
+    category: Option<&str>,
+    cached_values: impl Fn(&mut PriceCache) -> &mut CachedPrices<T>,
+    is_complete: impl Fn(&T) -> bool,
+    fetch: impl AsyncFnOnce(&[String]) -> Option<Vec<T>>,
+) -> Option<Vec<T>> {
+    let settings = price_cache_settings();
+    let now = unix_seconds();
+
+    let mut cache = load_price_cache();
+
+    let stale_ids = if settings.offline {
+        vec![]
+    } else {
+        let cached = cached_values(&mut cache);
+
+        ids.iter()
+            .filter(|&id| {
+                !cached.get(id).is_some_and(|cached| {
+                    cached.age(now) < settings.ttl_seconds
+                        && is_complete(&cached.value)
+                })
+            })
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+
+    if !stale_ids.is_empty() {
+        if let Some(values) = fetch(&stale_ids).await {
+            insert_cached(
+                cached_values(&mut cache),
+                &stale_ids,
+                category,
+                values,
+                now,
+            );
+
+            if !save_price_cache(&cache) {
+                eprintln!(
+                    "{}",
+                    "warning: can't save the price cache, look above for possible errors"
+                        .yellow()
+                );
+            }
+        } else {
+            eprintln!(
+                "{}",
+                "warning: can't fetch prices, using cached prices instead"
+                    .yellow()
+            );
+        }
+    }
+
+    let cached = cached_values(&mut cache);
+    let mut values = Vec::with_capacity(ids.len());
+
+    for id in ids {
+        let Some(cached) = cached.get(id) else {
+            eprintln!("{}{id}", "error: no cached price found for ".red());
+
+            return None;
+        };
+
+        if settings.offline || cached.age(now) >= settings.ttl_seconds {
+            println!(
+                "Using the cached price of {id}, fetched {} ago.",
+                format_age(cached.age(now)).bright_yellow()
+            );
+        }
+
+        values.push(cached.value.clone());
+    }
+
+    Some(values)
+}
+
+// Fetches the prices from the providers, recording them to the price history.
+#[inline]
+async fn fetch_and_record_lowest_bin_prices(
+    ids: &[String],
+    category: Option<&str>,
+) -> Option<Vec<Option<i64>>> {
+    let prices = fetch_from_providers(async |provider| {
+        provider.fetch_lowest_bin_prices(ids, category).await
+    })
+    .await?;
+
+    record_prices(&lowest_bin_records(ids, &prices, unix_seconds()));
+
+    Some(prices)
+}
+
+// Records the cheapest listing of each item as its lowest BIN.
+#[inline]
+async fn fetch_and_record_bin_listings(
+    ids: &[String],
+    category: Option<&str>,
+    depth: usize,
+) -> Option<Vec<BinListings>> {
+    let listings = fetch_from_providers(async |provider| {
+        provider.fetch_bin_listings(ids, category, depth).await
+    })
+    .await?;
+
+    let lowest_prices = listings
+        .iter()
+        .map(|listings| listings.prices.first().copied())
+        .collect::<Vec<_>>();
+
+    record_prices(&lowest_bin_records(ids, &lowest_prices, unix_seconds()));
+
+    Some(listings)
+}
+
+#[inline]
+async fn fetch_and_record_bazaar_products(
+    ids: &[String],
+) -> Option<Vec<Option<BazaarProduct>>> {
+    let products = fetch_from_providers(async |provider| {
+        provider.fetch_bazaar_products(ids).await
+    })
+    .await?;
+
+    record_prices(&bazaar_records(ids, &products, unix_seconds()));
+
+    Some(products)
+}
+
+// Tries the providers in order until one of them can be used.
+#[inline]
+async fn fetch_from_providers<T>(
+    fetch: impl AsyncFn(&Provider) -> Option<T>,
+) -> Option<T> {
+    for provider in price_providers() {
+        let fetched = fetch(provider).await;
+
+        if fetched.is_some() {
+            return fetched;
+        }
+
+        print_provider_failed(provider);
+    }
+
+    None
+}
+
+// Fetches every price in the cache again, regardless of their age.
+#[inline]
+pub(crate) async fn refresh_prices() -> bool {
+    if is_offline() {
+        eprintln!("{}", "error: can't refresh prices in offline mode".red());
+
+        return false;
+    }
+
+    let mut cache = load_price_cache();
+    let mut refreshed = 0;
+
+    // Fetched with the category they were cached with, so the same listings
+    // are searched as when they were first fetched.
+    for (category, ids) in cached_ids_by_category(cache.lowest_bin()) {
+        let Some(prices) =
+            fetch_and_record_lowest_bin_prices(&ids, category.as_deref())
+                .await
+        else {
+            eprintln!("{}", "error: can't fetch prices".red());
+
+            return false;
+        };
+
+        insert_cached(
+            cache.lowest_bin_mut(),
+            &ids,
+            category.as_deref(),
+            prices,
+            unix_seconds(),
+        );
+        refreshed += ids.len();
+    }
+
+    for (category, ids) in cached_ids_by_category(cache.bazaar()) {
+        let Some(products) = fetch_and_record_bazaar_products(&ids).await
+        else {
+            eprintln!("{}", "error: can't fetch Bazaar products".red());
+
+            return false;
+        };
+
+        insert_cached(
+            cache.bazaar_mut(),
+            &ids,
+            category.as_deref(),
+            products,
+            unix_seconds(),
+        );
+        refreshed += ids.len();
+    }
+
+    // As deep as the deepest cached listings, so none of them get shallower.
+    let depth = cache
+        .bin_listings()
+        .values()
+        .map(|listings| listings.value.prices.len())
+        .max()
+        .unwrap_or(1);
+
+    for (category, ids) in cached_ids_by_category(cache.bin_listings()) {
+        let Some(listings) =
+            fetch_and_record_bin_listings(&ids, category.as_deref(), depth)
+                .await
+        else {
+            eprintln!("{}", "error: can't fetch BIN listings".red());
+
+            return false;
+        };
+
+        insert_cached(
+            cache.bin_listings_mut(),
+            &ids,
+            category.as_deref(),
+            listings,
+            unix_seconds(),
+        );
+        refreshed += ids.len();
+    }
+
+    if !save_price_cache(&cache) {
+        return false;
+    }
+
+    if refreshed == 0 {
+        println!(
+            "There are no cached prices yet, they are cached when used by a calculator."
+        );
+    } else {
+        println!(
+            "Refreshed {} cached prices.",
+            refreshed.to_string().bright_green()
+        );
+    }
+
+    true
+}
+
+// Prints the price of every side and the volumes of the products.
+#[inline]
+pub(crate) async fn print_bazaar_products(ids: &[String]) -> bool {
+    let Some(products) = fetch_bazaar_products(ids).await else {
+        eprintln!("{}", "error: can't fetch Bazaar products".red());
+
+        return false;
+    };
+
+    for (id, product) in ids.iter().zip(products) {
+        println!("{}:", id.bright_blue());
+
+        let Some(product) = product else {
+            println!(" {}", "No such Bazaar product.".bright_red());
+
+            continue;
+        };
+
+        for side in [
+            BazaarSide::Buy,
+            BazaarSide::Sell,
+            BazaarSide::BuyOrder,
+            BazaarSide::SellOffer,
+        ] {
+            println!(
+                " {}: {}",
+                side.name(),
+                product.price(side).map_or_else(
+                    || "no orders".bright_red().to_string(),
+                    |price| format!("{price:.1} coins").yellow().to_string()
+                )
+            );
+        }
+
+        println!(
+            " {} items in sell offers, {} items in buy orders",
+            format_volume(product.buy_volume),
+            format_volume(product.sell_volume)
+        );
+        println!(
+            " {} items bought and {} items sold instantly in the last 7 days",
+            format_volume(product.buy_moving_week),
+            format_volume(product.sell_moving_week)
+        );
+    }
+
+    true
+}
+
+#[inline]
+#[must_use]
+fn format_volume(volume: Option<i64>) -> String {
+    volume.map_or_else(
+        || "unknown".to_owned(),
+        |volume| {
+            with_comma_separators(&volume.to_string())
+                .unwrap_or_else(|| volume.to_string())
+        },
+    )
+}
+
+// Used when the price can't be fetched, so the calculation can still go on.
+#[inline]
+#[must_use]
+pub(crate) fn ask_price_manually(name: &str) -> f64 {
+    eprintln!("{}{name}", "warning: can't get the price of ".yellow());
+
+    ask_float_input(
+        &format!("Enter the price of {name} manually: "),
+        Some(0.0),
+        None,
+    )
+}
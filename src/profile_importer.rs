@@ -0,0 +1,343 @@
+use core::fmt::Display;
+use core::time::Duration;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use colored::Colorize;
+use serde_json::Value;
+
+use crate::item_data::decompress_item_data;
+use crate::party_buffs::ALL_DUNGEON_CLASSES;
+use crate::utils::ask_int_input;
+use crate::utils::f64_to_i64;
+use crate::utils::read_file;
+use crate::utils::usize_to_i32;
+
+const MASTER_SKULL_ID_PREFIX: &[u8] = b"MASTER_SKULL_TIER_";
+
+static IMPORTED_PROFILE: OnceLock<ImportedProfile> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ProfileArguments {
+    // Path to a saved profile JSON, or an URL serving it.
+    pub(crate) source: String,
+    pub(crate) member: Option<String>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct ImportedProfile {
+    // Skill XP by lowercase skill name, i.e. "farming".
+    pub(crate) skill_xp: Vec<(String, i64)>,
+
+    pub(crate) catacombs_xp: Option<i64>,
+    // In the order of ALL_DUNGEON_CLASSES.
+    pub(crate) class_xp: Option<[f64; ALL_DUNGEON_CLASSES.len()]>,
+    pub(crate) secrets: Option<i32>,
+
+    // Highest Master Skull tier found in the accessory bag or inventory.
+    pub(crate) master_skull_tier: Option<i32>,
+}
+
+impl ImportedProfile {
+    #[inline]
+    #[must_use]
+    pub(crate) fn get_skill_xp(&self, skill_name: &str) -> Option<i64> {
+        self.skill_xp
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(skill_name))
+            .map(|&(_, xp)| xp)
+    }
+}
+
+// Returns the profile imported with --profile, if any.
+#[inline]
+#[must_use]
+pub(crate) fn imported_profile() -> Option<&'static ImportedProfile> {
+    IMPORTED_PROFILE.get()
+}
+
+#[inline]
+pub(crate) fn print_imported_value(name: &str, value: impl Display) {
+    println!(
+        "Using your {name} from the imported profile: {}",
+        value.to_string().bright_green()
+    );
+}
+
+// Uses the value from the imported profile if there is one, otherwise asks
+// for it.
+#[inline]
+pub(crate) fn imported_or_ask<T: Display>(
+    name: &str,
+    imported: impl FnOnce(&ImportedProfile) -> Option<T>,
+    ask: impl FnOnce() -> T,
+) -> T {
+    imported_profile().and_then(imported).map_or_else(ask, |value| {
+        print_imported_value(name, &value);
+
+        value
+    })
+}
+
+// Loads the profile and makes it available to the calculators, returning
+// false if it can't be imported.
+#[inline]
+pub(crate) async fn import_profile(arguments: &ProfileArguments) -> bool {
+    let Some(text) = load_profile_text(&arguments.source).await else {
+        return false;
+    };
+
+    let json = match serde_json::from_str::<Value>(&text) {
+        Ok(json) => json,
+
+        Err(e) => {
+            eprintln!("{}{e}", "error: can't parse profile JSON: ".red());
+
+            return false;
+        },
+    };
+
+    let members = match find_profile_members(&json) {
+        Ok(members) => members,
+
+        Err(e) => {
+            eprintln!("{}{e}", "error: can't import profile: ".red());
+
+            return false;
+        },
+    };
+
+    let Some(member) = select_member(&members, arguments.member.as_deref())
+    else {
+        return false;
+    };
+
+    let profile = extract_member_profile(member);
+
+    println!(
+        "{}",
+        "Imported the profile, the values in it will be used instead of asking."
+            .bright_green()
+    );
+
+    IMPORTED_PROFILE.set(profile).is_ok()
+}
+
+#[inline]
+async fn load_profile_text(source: &str) -> Option<String> {
+    if !source.starts_with("http://") && !source.starts_with("https://") {
+        return read_file(Path::new(source));
+    }
+
+    let client = match reqwest::ClientBuilder::new()
+        .timeout(Duration::from_secs(10))
+        .build()
+    {
+        Ok(client) => client,
+
+        Err(e) => {
+            eprintln!("{}{e}", "Error when building http client: ".red());
+
+            return None;
+        },
+    };
+
+    let response = match client.get(source).send().await {
+        Ok(response) => response,
+
+        Err(e) => {
+            eprintln!("{}{e}", "Error when getting response: ".red());
+
+            return None;
+        },
+    };
+
+    match response.text().await {
+        Ok(text) => Some(text),
+
+        Err(e) => {
+            eprintln!("{}{e}", "Error when getting response body: ".red());
+
+            None
+        },
+    }
+}
+
+// Accepts a single profile ({"profile": {...}}), a list of profiles
+// ({"profiles": [...]}, using the selected one) or the profile object itself.
+#[inline]
+pub(crate) fn find_profile_members(
+    json: &Value,
+) -> Result<Vec<(&str, &Value)>, String> {
+    let profile = if let Some(profile) = json.get("profile") {
+        profile
+    } else if let Some(profiles) =
+        json.get("profiles").and_then(Value::as_array)
+    {
+        profiles
+            .iter()
+            .find(|profile| {
+                profile.get("selected").and_then(Value::as_bool) == Some(true)
+            })
+            .or_else(|| profiles.first())
+            .ok_or("the profile list is empty")?
+    } else {
+        json
+    };
+
+    let members = profile
+        .get("members")
+        .and_then(Value::as_object)
+        .ok_or("can't find the members of the profile")?;
+
+    if members.is_empty() {
+        return Err("the profile has no members".to_owned());
+    }
+
+    Ok(members.iter().map(|(uuid, member)| (uuid.as_str(), member)).collect())
+}
+
+#[inline]
+#[must_use]
+fn select_member<'a>(
+    members: &[(&str, &'a Value)],
+    uuid: Option<&str>,
+) -> Option<&'a Value> {
+    if let Some(uuid) = uuid {
+        let normalized_uuid = uuid.replace('-', "").to_lowercase();
+
+        let member = members
+            .iter()
+            .find(|&&(member_uuid, _)| member_uuid == normalized_uuid)
+            .map(|&(_, member)| member);
+
+        if member.is_none() {
+            eprintln!(
+                "{}{uuid}",
+                "error: can't find a member of the profile with UUID ".red()
+            );
+        }
+
+        return member;
+    }
+
+    if let [(_, member)] = members {
+        return Some(member);
+    }
+
+    println!();
+    println!("The profile has several members, select yours: ");
+
+    for (index, (uuid, _)) in members.iter().enumerate() {
+        println!(" {}. {uuid}", (index + 1).to_string().bright_blue());
+    }
+
+    let selection = ask_int_input(
+        "Enter a number to select: ",
+        Some(1),
+        Some(usize_to_i32(members.len())),
+    );
+
+    usize::try_from(selection - 1)
+        .ok()
+        .and_then(|index| members.get(index))
+        .map(|&(_, member)| member)
+}
+
+#[inline]
+#[must_use]
+fn get_path<'a>(json: &'a Value, path: &[&str]) -> Option<&'a Value> {
+    path.iter().try_fold(json, |json, key| json.get(key))
+}
+
+#[inline]
+#[must_use]
+fn get_f64(json: &Value, path: &[&str]) -> Option<f64> {
+    get_path(json, path).and_then(Value::as_f64)
+}
+
+// Supports both the current API shape, where data is grouped under
+// player_data, dungeons and inventory, and the older flat one.
+#[inline]
+#[must_use]
+pub(crate) fn extract_member_profile(member: &Value) -> ImportedProfile {
+    // i.e. player_data.experience.SKILL_FARMING in the current format and
+    // experience_skill_farming in the older one.
+    let (experience, skill_prefix) =
+        get_path(member, &["player_data", "experience"])
+            .and_then(Value::as_object)
+            .map_or((member.as_object(), "experience_skill_"), |experience| {
+                (Some(experience), "SKILL_")
+            });
+
+    let skill_xp = experience
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, xp)| {
+            Some((
+                key.strip_prefix(skill_prefix)?.to_lowercase(),
+                f64_to_xp(xp.as_f64()?),
+            ))
+        })
+        .collect();
+
+    let class_xp = ALL_DUNGEON_CLASSES.map(|class| {
+        get_f64(
+            member,
+            &[
+                "dungeons",
+                "player_classes",
+                &class.to_string().to_lowercase(),
+                "experience",
+            ],
+        )
+    });
+
+    ImportedProfile {
+        skill_xp,
+
+        catacombs_xp: get_f64(
+            member,
+            &["dungeons", "dungeon_types", "catacombs", "experience"],
+        )
+        .map(f64_to_xp),
+        class_xp: class_xp
+            .iter()
+            .any(Option::is_some)
+            .then(|| class_xp.map(|xp| xp.unwrap_or(0.0))),
+        secrets: get_f64(member, &["dungeons", "secrets"])
+            .and_then(|secrets| i32::try_from(f64_to_xp(secrets)).ok()),
+
+        master_skull_tier: [
+            &["inventory", "bag_contents", "talisman_bag", "data"][..],
+            &["inventory", "inv_contents", "data"],
+            &["talisman_bag", "data"],
+            &["inv_contents", "data"],
+        ]
+        .iter()
+        .filter_map(|path| get_path(member, path).and_then(Value::as_str))
+        .filter_map(find_master_skull_tier)
+        .max(),
+    }
+}
+
+#[inline]
+#[must_use]
+fn f64_to_xp(xp: f64) -> i64 {
+    f64_to_i64(xp.trunc())
+}
+
+// Item data is base64 encoded gzipped NBT. Instead of fully parsing the NBT,
+// this looks for the Master Skull item IDs in it, which are stored as plain
+// text.
+#[inline]
+#[must_use]
+pub(crate) fn find_master_skull_tier(item_data: &str) -> Option<i32> {
+    decompress_item_data(item_data)?
+        .windows(MASTER_SKULL_ID_PREFIX.len() + 1)
+        .filter(|window| window.starts_with(MASTER_SKULL_ID_PREFIX))
+        .filter_map(|window| window.last())
+        .filter(|tier| tier.is_ascii_digit())
+        .map(|&tier| i32::from(tier - b'0'))
+        .max()
+}
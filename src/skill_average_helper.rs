@@ -1,22 +1,17 @@
 use std::time::Instant;
 
+use colored::Colorize;
+
+use crate::constants::SKILL_XP_PER_LEVEL;
+use crate::profile_importer::imported_or_ask;
+use crate::profile_importer::imported_profile;
 use crate::utils;
 
 #[inline]
 pub(crate) fn skill_average_helper(
     start_without_user_input: &mut Option<Instant>,
 ) -> bool {
-    let mut skills: Vec<Skill> = Vec::new();
-
-    for skill in Skill::ALL_SKILLS {
-        let current_level = utils::ask_int_input(
-            format!("What is your {} level?: ", skill.name).as_str(),
-            Some(0),
-            Some(skill.max_level),
-        );
-
-        skills.push(Skill { level: current_level, ..skill });
-    }
+    let skills = ask_skills();
 
     let max_skill_average = utils::mean(
         &Skill::ALL_SKILLS
@@ -31,6 +26,7 @@ pub(crate) fn skill_average_helper(
         Some(0.0),
         Some(max_skill_average),
     );
+    let plan_rates = ask_plan_rates();
 
     *start_without_user_input = Some(Instant::now());
 
@@ -39,10 +35,17 @@ pub(crate) fn skill_average_helper(
             .unwrap_or(0.0);
 
     println!(
-        "Your Skill Average is {skill_average}. Max Skill Average is {max_skill_average}."
+        "Your Skill Average is {skill_average} ({:.2} including progress towards the next levels). Max Skill Average is {max_skill_average}.",
+        calculate_fractional_skill_average(&skills)
     );
     println!();
 
+    print_least_xp_for_target_average(&skills, target_skill_average);
+
+    if let Some((goal, rates)) = plan_rates {
+        print_optimized_plan(&skills, target_skill_average, goal, &rates);
+    }
+
     let skill_levels_required_for_target_average =
         get_skill_levels_required_for_target_average(
             &skills,
@@ -128,6 +131,407 @@ pub(crate) fn skill_average_helper(
     true
 }
 
+#[inline]
+#[must_use]
+fn ask_skills() -> Vec<Skill> {
+    let all_imported = imported_profile().is_some_and(|profile| {
+        Skill::ALL_SKILLS
+            .iter()
+            .all(|skill| profile.get_skill_xp(skill.name).is_some())
+    });
+
+    // Only asked when some skill is missing from the imported profile.
+    let input_xp = all_imported || {
+        println!();
+        println!(" {}. Enter Skill XP", "1".bright_blue());
+        println!(
+            " {}. Enter Skill levels (decimals allowed, e.g. 45.5)",
+            "2".bright_blue()
+        );
+
+        utils::ask_int_input("Enter a number to select: ", Some(1), Some(2))
+            == 1
+    };
+
+    Skill::ALL_SKILLS
+        .iter()
+        .map(|&skill| {
+            let xp = imported_or_ask(
+                &format!("{} XP", skill.name),
+                |profile| profile.get_skill_xp(skill.name),
+                || {
+                    if input_xp {
+                        utils::f64_to_i64(
+                            utils::ask_float_input(
+                                format!("What is your {} XP?: ", skill.name)
+                                    .as_str(),
+                                Some(0.0),
+                                None,
+                            )
+                            .trunc(),
+                        )
+                    } else {
+                        get_skill_xp_for_fractional_level(
+                            utils::ask_float_input(
+                                format!(
+                                    "What is your {} level?: ",
+                                    skill.name
+                                )
+                                .as_str(),
+                                Some(0.0),
+                                Some(f64::from(skill.max_level)),
+                            ),
+                        )
+                    }
+                },
+            );
+
+            skill.with_xp(xp)
+        })
+        .collect()
+}
+
+#[inline]
+fn print_least_xp_for_target_average(
+    skills: &[Skill],
+    target_skill_average: f64,
+) {
+    let Some(target_skills) =
+        plan_least_xp_for_target_average(skills, target_skill_average)
+    else {
+        println!(
+            "{}",
+            "Your target Skill Average can't be reached.".bright_red()
+        );
+        println!();
+
+        return;
+    };
+
+    let total_xp_needed = target_skills
+        .iter()
+        .zip(skills)
+        .map(|(target_skill, skill)| target_skill.xp - skill.xp)
+        .sum::<i64>();
+
+    println!(
+        "The least XP needed to reach your target Skill Average is {} XP, leveling:",
+        utils::format_coins(total_xp_needed).bright_green()
+    );
+
+    for (target_skill, skill) in target_skills.iter().zip(skills) {
+        if target_skill.level > skill.level {
+            println!(
+                "{} {} -> {} ({} XP)",
+                skill.name,
+                skill.level,
+                target_skill.level,
+                utils::format_coins(target_skill.xp - skill.xp).yellow()
+            );
+        }
+    }
+
+    println!();
+}
+
+#[inline]
+#[must_use]
+fn ask_plan_rates() -> Option<(PlanGoal, Vec<f64>)> {
+    println!();
+    println!(" {}. Only show the least XP needed", "1".bright_blue());
+    println!(
+        " {}. Plan the fastest way (enter XP per hour for each skill)",
+        "2".bright_blue()
+    );
+    println!(
+        " {}. Plan the cheapest way (enter coins per XP for each skill, e.g. Alchemy and Enchanting)",
+        "3".bright_blue()
+    );
+
+    let goal = match utils::ask_int_input(
+        "Enter a number to select: ",
+        Some(1),
+        Some(3),
+    ) {
+        2 => PlanGoal::Fastest,
+        3 => PlanGoal::Cheapest,
+
+        _ => return None,
+    };
+
+    let rates = Skill::ALL_SKILLS
+        .iter()
+        .map(|skill| {
+            let rate = utils::ask_float_input(
+                &match goal {
+                    PlanGoal::Fastest => format!(
+                        "How much {} XP do you get per hour? (Enter 0 to not level it): ",
+                        skill.name
+                    ),
+                    PlanGoal::Cheapest => format!(
+                        "How many coins does 1 {} XP cost? (Enter 0 if you can't buy it): ",
+                        skill.name
+                    ),
+                },
+                Some(0.0),
+                None,
+            );
+
+            // Cost per XP, 0 meaning the skill won't be leveled.
+            match goal {
+                PlanGoal::Fastest if rate > 0.0 => 1.0 / rate,
+                PlanGoal::Fastest => 0.0,
+                PlanGoal::Cheapest => rate,
+            }
+        })
+        .collect();
+
+    Some((goal, rates))
+}
+
+#[inline]
+fn print_optimized_plan(
+    skills: &[Skill],
+    target_skill_average: f64,
+    goal: PlanGoal,
+    cost_per_xp: &[f64],
+) {
+    let format_cost = |cost: f64| match goal {
+        PlanGoal::Fastest => format!("{cost:.1} hours"),
+        PlanGoal::Cheapest =>
+            format!("{} coins", utils::format_coins_f64(cost)),
+    };
+
+    let Some(level_ups) =
+        plan_cheapest_level_ups(skills, target_skill_average, cost_per_xp)
+    else {
+        println!(
+            "{}",
+            "Your target Skill Average can't be reached leveling only the skills you entered a rate for."
+                .bright_red()
+        );
+        println!();
+
+        return;
+    };
+
+    println!(
+        "The {} plan to reach your target Skill Average, step by step:",
+        match goal {
+            PlanGoal::Fastest => "fastest",
+            PlanGoal::Cheapest => "cheapest",
+        }
+    );
+
+    let mut step = 0;
+
+    for group in level_ups.chunk_by(|a, b| a.skill == b.skill) {
+        let (Some(first), Some(last)) = (group.first(), group.last()) else {
+            continue;
+        };
+
+        step += 1;
+
+        println!(
+            " {}. {} {} -> {} ({} XP, {})",
+            step.to_string().bright_blue(),
+            skills[first.skill].name,
+            first.level - 1,
+            last.level,
+            utils::format_coins(
+                group.iter().map(|level_up| level_up.xp).sum()
+            ),
+            format_cost(group.iter().map(|level_up| level_up.cost).sum())
+                .yellow()
+        );
+    }
+
+    println!();
+    println!("Target levels:");
+
+    for (target_skill, skill) in
+        apply_level_ups(skills, &level_ups).iter().zip(skills)
+    {
+        if target_skill.level > skill.level {
+            println!(
+                "{} {} -> {}",
+                skill.name, skill.level, target_skill.level
+            );
+        }
+    }
+
+    println!();
+    println!(
+        "In total it takes {}.",
+        format_cost(level_ups.iter().map(|level_up| level_up.cost).sum())
+            .bright_green()
+    );
+    println!();
+}
+
+// Total XP needed to reach the level from level 0.
+#[inline]
+#[must_use]
+pub(crate) fn get_skill_xp_for_level(level: i32) -> i64 {
+    SKILL_XP_PER_LEVEL.iter().take(usize::try_from(level).unwrap_or(0)).sum()
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn get_skill_level(xp: i64, max_level: i32) -> i32 {
+    let mut remaining_xp = xp;
+
+    for (level, &xp_for_level) in SKILL_XP_PER_LEVEL.iter().enumerate() {
+        let level = utils::usize_to_i32(level);
+
+        if level >= max_level || remaining_xp < xp_for_level {
+            return level;
+        }
+
+        remaining_xp -= xp_for_level;
+    }
+
+    utils::usize_to_i32(SKILL_XP_PER_LEVEL.len()).min(max_level)
+}
+
+// Level including the progress towards the next level, i.e. 24.5 when half
+// way from level 24 to 25, capped at the maximum level.
+#[inline]
+#[must_use]
+pub(crate) fn get_skill_level_with_progress(xp: i64, max_level: i32) -> f64 {
+    let level = get_skill_level(xp, max_level);
+
+    if level >= max_level {
+        return f64::from(max_level);
+    }
+
+    let xp_for_level = get_skill_xp_for_level(level);
+    let xp_for_next_level = get_skill_xp_for_level(level + 1);
+
+    f64::from(level)
+        + utils::i64_to_f64(xp - xp_for_level)
+            / utils::i64_to_f64(xp_for_next_level - xp_for_level)
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn get_skill_xp_for_fractional_level(level: f64) -> i64 {
+    let whole_level = utils::f64_to_i32(level.trunc());
+    let xp_for_level = get_skill_xp_for_level(whole_level);
+    let xp_for_next_level = get_skill_xp_for_level(whole_level + 1);
+
+    xp_for_level
+        + utils::f64_to_i64(
+            (utils::i64_to_f64(xp_for_next_level - xp_for_level)
+                * level.fract())
+            .trunc(),
+        )
+}
+
+// The Skill Average third party tools show, counting the progress towards
+// the next levels. The game itself only counts whole levels.
+#[inline]
+#[must_use]
+pub(crate) fn calculate_fractional_skill_average(skills: &[Skill]) -> f64 {
+    if skills.is_empty() {
+        return 0.0;
+    }
+
+    skills
+        .iter()
+        .map(|skill| get_skill_level_with_progress(skill.xp, skill.max_level))
+        .sum::<f64>()
+        / utils::usize_to_f64(skills.len())
+}
+
+// Returns the skills at the levels reaching the target Skill Average (counting
+// whole levels, like the game does) with the least total XP, or None if the
+// target can't be reached.
+#[inline]
+#[must_use]
+pub(crate) fn plan_least_xp_for_target_average(
+    skills: &[Skill],
+    target_skill_average: f64,
+) -> Option<Vec<Skill>> {
+    plan_cheapest_level_ups(
+        skills,
+        target_skill_average,
+        &vec![1.0; skills.len()],
+    )
+    .map(|level_ups| apply_level_ups(skills, &level_ups))
+}
+
+// Returns the level ups reaching the target Skill Average (counting whole
+// levels, like the game does) with the least total cost, in the order they
+// should be done, or None if the target can't be reached. The cost of each
+// level up is the XP it needs times the cost per XP of the skill, and skills
+// with a cost per XP of 0 or less are never leveled. Since every level needs
+// at least as much XP as the previous one, always doing the cheapest next
+// level up is optimal.
+#[inline]
+#[must_use]
+pub(crate) fn plan_cheapest_level_ups(
+    skills: &[Skill],
+    target_skill_average: f64,
+    cost_per_xp: &[f64],
+) -> Option<Vec<SkillLevelUp>> {
+    let levels_needed =
+        utils::f64_to_i32(
+            (target_skill_average * utils::usize_to_f64(skills.len())).ceil(),
+        ) - skills.iter().map(|skill| skill.level).sum::<i32>();
+
+    let mut target_skills = skills.to_vec();
+    let mut level_ups = Vec::new();
+
+    for _ in 0..levels_needed {
+        let (index, xp, cost) = target_skills
+            .iter()
+            .zip(cost_per_xp)
+            .enumerate()
+            .filter(|&(_, (skill, &cost_per_xp))| {
+                skill.level < skill.max_level && cost_per_xp > 0.0
+            })
+            .map(|(index, (skill, &cost_per_xp))| {
+                let xp = get_skill_xp_for_level(skill.level + 1) - skill.xp;
+
+                (index, xp, utils::i64_to_f64(xp) * cost_per_xp)
+            })
+            .min_by(|(_, _, a), (_, _, b)| a.total_cmp(b))?;
+
+        let skill = &mut target_skills[index];
+
+        skill.level += 1;
+        skill.xp += xp;
+
+        level_ups.push(SkillLevelUp {
+            skill: index,
+            level: skill.level,
+            xp,
+            cost,
+        });
+    }
+
+    Some(level_ups)
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn apply_level_ups(
+    skills: &[Skill],
+    level_ups: &[SkillLevelUp],
+) -> Vec<Skill> {
+    let mut target_skills = skills.to_vec();
+
+    for level_up in level_ups {
+        if let Some(skill) = target_skills.get_mut(level_up.skill) {
+            skill.level = level_up.level;
+            skill.xp += level_up.xp;
+        }
+    }
+
+    target_skills
+}
+
 #[inline]
 #[must_use]
 fn get_skill_levels_required_for_target_average(
@@ -154,17 +558,36 @@ fn get_skill_levels_required_for_target_average(
     skill_levels_needed
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum PlanGoal {
+    Fastest,
+    Cheapest,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct SkillLevelUp {
+    // Index of the skill in the list of skills.
+    pub(crate) skill: usize,
+    // Level reached after the level up.
+    pub(crate) level: i32,
+
+    pub(crate) xp: i64,
+    pub(crate) cost: f64,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Hash)]
-struct Skill {
-    name: &'static str,
+pub(crate) struct Skill {
+    pub(crate) name: &'static str,
+
+    pub(crate) level: i32,
+    pub(crate) max_level: i32,
 
-    level: i32,
-    max_level: i32,
+    pub(crate) xp: i64,
 }
 
 impl Skill {
     const ALCHEMY: Self = Self::new("Alchemy", 0, 50);
-    const ALL_SKILLS: [Self; 9] = [
+    pub(crate) const ALL_SKILLS: [Self; 9] = [
         Self::FARMING,
         Self::MINING,
         Self::COMBAT,
@@ -187,6 +610,12 @@ impl Skill {
     #[inline]
     #[must_use]
     const fn new(name: &'static str, level: i32, max_level: i32) -> Self {
-        Self { name, level, max_level }
+        Self { name, level, max_level, xp: 0 }
+    }
+
+    #[inline]
+    #[must_use]
+    pub(crate) fn with_xp(self, xp: i64) -> Self {
+        Self { level: get_skill_level(xp, self.max_level), xp, ..self }
     }
 }
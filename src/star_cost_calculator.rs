@@ -0,0 +1,239 @@
+use std::time::Instant;
+
+use colored::Colorize;
+
+use crate::catacombs_stat_boost_calculator::get_stars_stat_boost;
+use crate::prices::ask_bazaar_side;
+use crate::prices::ask_price_manually;
+use crate::prices::fetch_bazaar_prices;
+use crate::utils::ask_int_input;
+use crate::utils::format_coins_f64;
+use crate::utils::i64_to_f64;
+use crate::utils::usize_to_i32;
+use crate::utils::with_comma_separators;
+
+const MAXIMUM_STARS: i32 = 5;
+
+const ESSENCE_TYPES: [&str; 8] = [
+    "Wither", "Undead", "Dragon", "Spider", "Ice", "Diamond", "Gold",
+    "Crimson",
+];
+
+// Bazaar product IDs and names of the master stars, in order.
+const MASTER_STARS: [(&str, &str); 5] = [
+    ("FIRST_MASTER_STAR", "First Master Star"),
+    ("SECOND_MASTER_STAR", "Second Master Star"),
+    ("THIRD_MASTER_STAR", "Third Master Star"),
+    ("FOURTH_MASTER_STAR", "Fourth Master Star"),
+    ("FIFTH_MASTER_STAR", "Fifth Master Star"),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct StarUpgradeCost {
+    pub(crate) essence: i64,
+    pub(crate) essence_cost: f64,
+    pub(crate) master_stars_cost: f64,
+}
+
+impl StarUpgradeCost {
+    #[inline]
+    #[must_use]
+    pub(crate) fn total(&self) -> f64 {
+        self.essence_cost + self.master_stars_cost
+    }
+}
+
+#[inline]
+pub(crate) async fn star_cost_calculator(
+    start_without_user_input: &mut Option<Instant>,
+) -> bool {
+    let current_stars = ask_int_input(
+        "Enter the amount of normal stars your item has: ",
+        Some(0),
+        Some(MAXIMUM_STARS),
+    );
+    let planned_stars = ask_int_input(
+        "Enter the amount of normal stars you plan your item to have: ",
+        Some(current_stars),
+        Some(MAXIMUM_STARS),
+    );
+    let (current_master_stars, planned_master_stars) =
+        ask_master_stars(current_stars, planned_stars);
+
+    let (essence_type, essence_per_star) = if planned_stars > current_stars {
+        ask_essence_per_star(current_stars, planned_stars)
+    } else {
+        ("Wither", vec![])
+    };
+
+    let side = ask_bazaar_side(true);
+
+    *start_without_user_input = Some(Instant::now());
+
+    let master_stars = MASTER_STARS
+        .iter()
+        .skip(usize::try_from(current_master_stars).unwrap_or(0))
+        .take(
+            usize::try_from(planned_master_stars - current_master_stars)
+                .unwrap_or(0),
+        )
+        .collect::<Vec<_>>();
+
+    let essence_id = format!("ESSENCE_{}", essence_type.to_uppercase());
+    let essence_name = format!("{essence_type} Essence");
+
+    let mut ids =
+        master_stars.iter().map(|&&(id, _)| id.to_owned()).collect::<Vec<_>>();
+
+    if !essence_per_star.is_empty() {
+        ids.push(essence_id);
+    }
+
+    let prices = if ids.is_empty() {
+        vec![]
+    } else {
+        fetch_bazaar_prices(&ids, side).await.unwrap_or_default()
+    };
+
+    let mut asked_prices_manually = false;
+    let mut price_or_ask = |index: usize, name: &str| {
+        prices.get(index).copied().flatten().unwrap_or_else(|| {
+            asked_prices_manually = true;
+
+            ask_price_manually(name)
+        })
+    };
+
+    let master_star_prices = master_stars
+        .iter()
+        .enumerate()
+        .map(|(index, &&(_, name))| price_or_ask(index, name))
+        .collect::<Vec<_>>();
+
+    let essence_price = if essence_per_star.is_empty() {
+        0.0
+    } else {
+        price_or_ask(master_stars.len(), &essence_name)
+    };
+
+    // Entering prices is user input, so it's not counted.
+    if asked_prices_manually {
+        *start_without_user_input = Some(Instant::now());
+    }
+
+    let cost = calculate_star_upgrade_cost(
+        &essence_per_star,
+        essence_price,
+        &master_star_prices,
+    );
+
+    let stat_boost_gain =
+        get_stars_stat_boost(planned_stars, planned_master_stars)
+            - get_stars_stat_boost(current_stars, current_master_stars);
+
+    println!();
+
+    if !essence_per_star.is_empty() {
+        println!(
+            "You need {} {essence_name}, costing {} coins.",
+            with_comma_separators(&cost.essence.to_string())
+                .unwrap_or_else(|| cost.essence.to_string()),
+            format_coins_f64(cost.essence_cost).yellow()
+        );
+    }
+
+    for (&&(_, name), price) in master_stars.iter().zip(&master_star_prices) {
+        println!("{name} costs {} coins.", format_coins_f64(*price).yellow());
+    }
+
+    println!();
+    println!(
+        "Going from {current_stars} stars and {current_master_stars} master stars to {planned_stars} stars and {planned_master_stars} master stars costs {} coins in total, giving {} more stat boost in Dungeons.",
+        format_coins_f64(cost.total()).bright_green(),
+        format!("{stat_boost_gain}%").bright_green()
+    );
+
+    true
+}
+
+// Master stars can only be applied to items with every normal star.
+#[inline]
+#[must_use]
+fn ask_master_stars(current_stars: i32, planned_stars: i32) -> (i32, i32) {
+    let current_master_stars = if current_stars == MAXIMUM_STARS {
+        ask_int_input(
+            "Enter the amount of master stars your item has: ",
+            Some(0),
+            Some(MAXIMUM_STARS),
+        )
+    } else {
+        0
+    };
+    let planned_master_stars = if planned_stars == MAXIMUM_STARS {
+        ask_int_input(
+            "Enter the amount of master stars you plan your item to have: ",
+            Some(current_master_stars),
+            Some(MAXIMUM_STARS),
+        )
+    } else {
+        0
+    };
+
+    (current_master_stars, planned_master_stars)
+}
+
+#[inline]
+#[must_use]
+fn ask_essence_per_star(
+    current_stars: i32,
+    planned_stars: i32,
+) -> (&'static str, Vec<i64>) {
+    println!();
+    println!("Select the essence type your item uses: ");
+
+    for (index, essence_type) in ESSENCE_TYPES.iter().enumerate() {
+        println!(" {}. {essence_type}", (index + 1).to_string().bright_blue());
+    }
+
+    let selection = ask_int_input(
+        "Enter a number to select: ",
+        Some(1),
+        Some(usize_to_i32(ESSENCE_TYPES.len())),
+    );
+
+    let essence_type = usize::try_from(selection - 1)
+        .ok()
+        .and_then(|index| ESSENCE_TYPES.get(index))
+        .copied()
+        .unwrap_or("Wither");
+
+    let essence_per_star = (current_stars + 1..=planned_stars)
+        .map(|star| {
+            i64::from(ask_int_input(
+                &format!(
+                    "How much {essence_type} Essence does star {star} need?: "
+                ),
+                Some(0),
+                None,
+            ))
+        })
+        .collect();
+
+    (essence_type, essence_per_star)
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn calculate_star_upgrade_cost(
+    essence_per_star: &[i64],
+    essence_price: f64,
+    master_star_prices: &[f64],
+) -> StarUpgradeCost {
+    let essence = essence_per_star.iter().sum::<i64>();
+
+    StarUpgradeCost {
+        essence,
+        essence_cost: i64_to_f64(essence) * essence_price,
+        master_stars_cost: master_star_prices.iter().sum(),
+    }
+}
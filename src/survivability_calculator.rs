@@ -1,49 +1,146 @@
+use core::cmp;
+use core::fmt;
+use core::fmt::Write as _;
+
+use std::path::Path;
 use std::time::Instant;
 
 use colored::Colorize;
 
-use crate::constants::F3_SHADOW_ASSASSIN_DAMAGE;
-use crate::constants::F4_SHADOW_ASSASSIN_DAMAGE;
-use crate::constants::F5_FELS_DAMAGE;
-use crate::constants::F5_SHADOW_ASSASSIN_DAMAGE;
-use crate::constants::F6_FELS_DAMAGE;
-use crate::constants::F6_SHADOW_ASSASSIN_DAMAGE;
-use crate::constants::F7_FELS_DAMAGE;
-use crate::constants::F7_SHADOW_ASSASSIN_DAMAGE;
-use crate::constants::M3_SHADOW_ASSASSIN_DAMAGE;
-use crate::constants::M4_SHADOW_ASSASSIN_DAMAGE;
-use crate::constants::M5_FELS_DAMAGE;
-use crate::constants::M5_SHADOW_ASSASSIN_DAMAGE;
-use crate::constants::M6_FELS_DAMAGE;
-use crate::constants::M6_SHADOW_ASSASSIN_DAMAGE;
-use crate::constants::M7_FELS_DAMAGE;
-use crate::constants::M7_SHADOW_ASSASSIN_DAMAGE;
-use crate::constants::VOIDGLOOM_SERAPH_TIER_1_TOTAL_DAMAGE;
-use crate::constants::VOIDGLOOM_SERAPH_TIER_2_TOTAL_DAMAGE;
-use crate::constants::VOIDGLOOM_SERAPH_TIER_3_TOTAL_DAMAGE;
-use crate::constants::VOIDGLOOM_SERAPH_TIER_4_TOTAL_DAMAGE;
+use crate::enemy_database::SelectedEnemy;
+use crate::enemy_database::select_enemy;
+use crate::loadout_optimizer::loadout_optimizer;
+use crate::party_buffs::PartyBuffs;
+use crate::party_buffs::ask_party;
+use crate::party_buffs::calculate_effective_health_with_party_buffs;
+use crate::party_buffs::calculate_party_buffs;
+use crate::party_buffs::party_buffs_report;
+use crate::party_buffs::print_party_buffs_note;
+use crate::utils::ask_float_input;
 use crate::utils::ask_int_input;
-use crate::utils::compare_f64;
+use crate::utils::ask_string_input;
 use crate::utils::f64_to_i32;
 use crate::utils::with_comma_separators;
+use crate::utils::write_file;
+
+// Minecraft runs at 20 ticks per second.
+const TICKS_PER_SECOND: i32 = 20;
+
+// Time to death simulations stop after 10 minutes of in-game time, surviving
+// that long with non-negative net healing means surviving indefinitely.
+const MAXIMUM_SIMULATED_TICKS: i32 = 10 * 60 * TICKS_PER_SECOND;
+
+// Bigger grids don't fit into a terminal anyway.
+const MAXIMUM_GRID_AXIS_LENGTH: usize = 50;
+const GRID_CELL_WIDTH: usize = 9;
+
+// Source: https://wiki.hypixel.net/Wither_Shield
+const WITHER_SHIELD_DURATION_TICKS: i32 = 5 * TICKS_PER_SECOND;
+const WITHER_SHIELD_COOLDOWN_TICKS: i32 = 10 * TICKS_PER_SECOND;
+const WITHER_SHIELD_DAMAGE_REDUCTION_PERCENT: f64 = 10.0;
+const WITHER_SHIELD_HEALING_CONVERSION_PERCENT: f64 = 50.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct SurvivalParameters {
+    pub(crate) health: i32,
+    pub(crate) defense: i32,
+
+    pub(crate) enemy_damage_per_hit: i32,
+    pub(crate) enemy_hit_interval_ticks: i32,
+
+    pub(crate) health_regen_per_second: f64,
+    pub(crate) healing_per_second: f64,
+    pub(crate) healing_bonus_percent: f64,
+
+    // 0 if Wither Shield is not used.
+    pub(crate) wither_shield_absorption: i32,
+
+    // From party buffs, the absorption is only granted once when the fight
+    // starts.
+    pub(crate) damage_reduction_percent: f64,
+    pub(crate) absorption: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct SurvivalResult {
+    // None if still alive when the simulation ended.
+    pub(crate) ticks_survived: Option<i32>,
+
+    // Highest incoming damage per second (before Defense) that your healing
+    // can keep up with.
+    pub(crate) sustainable_damage_per_second: f64,
+    pub(crate) incoming_damage_per_second: f64,
+
+    pub(crate) survives_indefinitely: bool,
+}
+
+#[inline]
+fn print_modes() {
+    println!();
+    println!("Select what you want to calculate: ");
+    println!(
+        " {}. Hits to die and Effective Health milestones",
+        "1".bright_blue()
+    );
+    println!(" {}. Time to death simulation", "2".bright_blue());
+    println!(" {}. Gear loadout optimizer", "3".bright_blue());
+    println!(" {}. Effective Health grid report", "4".bright_blue());
+    println!(
+        " {}. Party buffs against Shadow Assassins and Fels",
+        "5".bright_blue()
+    );
+}
 
 #[inline]
 pub(crate) fn survivability_calculator(
     start_without_user_input: &mut Option<Instant>,
 ) -> bool {
-    println!();
-    println!("Select your enemy: ");
-    println!(" {}. Shadow Assassin", "1".bright_blue(),);
-    println!(" {}. Fels", "2".bright_blue(),);
-    println!(" {}. Voidgloom Seraph", "3".bright_blue(),);
-    println!(" {}. Custom", "4".bright_blue());
+    print_modes();
 
-    let selection =
-        ask_int_input("Enter a number to select: ", Some(1), Some(4));
-    let enemy_damage_per_hit = get_enemy_damage_per_hit(selection);
+    let mode = ask_int_input("Enter a number to select: ", Some(1), Some(5));
+
+    match mode {
+        1 => hits_to_die_calculator(start_without_user_input),
+        2 => time_to_death_simulator(start_without_user_input),
+        3 => loadout_optimizer(start_without_user_input),
+        4 => effective_health_grid_report(start_without_user_input),
+        5 => party_buffs_report(start_without_user_input),
+
+        _ => {
+            eprintln!("{}{mode}", "error: invalid selection: ".red());
+
+            false
+        },
+    }
+}
+
+// True damage ignores Defense, so there's no point in asking for it.
+#[inline]
+#[must_use]
+pub(crate) fn ask_defense(enemy: SelectedEnemy) -> i32 {
+    if enemy.ignores_defense {
+        println!(
+            "{} Your enemy deals true damage, which ignores Defense.",
+            "Note:".red()
+        );
+
+        return 0;
+    }
+
+    ask_int_input("What's your Defense?: ", Some(0), None)
+}
+
+#[inline]
+fn hits_to_die_calculator(
+    start_without_user_input: &mut Option<Instant>,
+) -> bool {
+    let Some(enemy) = select_enemy() else {
+        return false;
+    };
+    let enemy_damage_per_hit = enemy.damage_per_hit;
 
     let health = ask_int_input("What's your Health?: ", Some(100), None);
-    let defense = ask_int_input("What's your Defense?: ", Some(0), None);
+    let defense = ask_defense(enemy);
 
     let effective_health = calculate_effective_health(health, defense);
     let hits_to_die =
@@ -55,10 +152,11 @@ pub(crate) fn survivability_calculator(
         None,
     );
 
+    let party = ask_party();
+
     *start_without_user_input = Some(Instant::now());
 
-    let wither_shield_health =
-        f64_to_i32((f64::from(crit_damage) * 1.5).trunc());
+    let wither_shield_health = calculate_wither_shield_health(crit_damage);
     let total_health_with_wither_shield = health + wither_shield_health;
 
     let effective_health_with_wither_shield =
@@ -88,17 +186,109 @@ pub(crate) fn survivability_calculator(
         hits_to_die_with_wither_shield.trunc(),
     );
 
-    let next_effective_health_milestone =
-        calculate_next_effective_health_milestone(
-            effective_health,
+    if !party.is_empty() {
+        print_hits_to_die_with_party_buffs(
+            health,
+            total_health_with_wither_shield,
+            defense,
             enemy_damage_per_hit,
+            calculate_party_buffs(&party, health),
         );
+    }
+
+    print_needed_stats(
+        enemy,
+        health,
+        defense,
+        crit_damage,
+        enemy_damage_per_hit,
+    )
+}
+
+#[inline]
+fn print_hits_to_die_with_party_buffs(
+    health: i32,
+    total_health_with_wither_shield: i32,
+    defense: i32,
+    enemy_damage_per_hit: i32,
+    buffs: PartyBuffs,
+) {
+    let effective_health =
+        calculate_effective_health_with_party_buffs(health, defense, buffs);
+    let effective_health_with_wither_shield =
+        calculate_effective_health_with_party_buffs(
+            total_health_with_wither_shield,
+            defense,
+            buffs,
+        );
+
+    println!();
+    print_party_buffs_note();
+    println!(
+        "With your party's buffs ({} absorption and {:.2}% damage reduction), you have {} Effective Health, taking {} hits to die, or {} Effective Health and {} hits to die with Wither Shield.",
+        buffs.absorption,
+        buffs.damage_reduction_percent,
+        with_comma_separators(&effective_health.to_string())
+            .unwrap_or_else(|| effective_health.to_string()),
+        calculate_hits_to_die(effective_health, enemy_damage_per_hit)
+            .trunc()
+            .to_string()
+            .bright_green(),
+        with_comma_separators(
+            &effective_health_with_wither_shield.to_string()
+        )
+        .unwrap_or_else(|| effective_health_with_wither_shield.to_string()),
+        calculate_hits_to_die(
+            effective_health_with_wither_shield,
+            enemy_damage_per_hit
+        )
+        .trunc()
+        .to_string()
+        .bright_green()
+    );
+}
+
+#[inline]
+fn print_needed_stats(
+    enemy: SelectedEnemy,
+    health: i32,
+    defense: i32,
+    crit_damage: i32,
+    enemy_damage_per_hit: i32,
+) -> bool {
+    let total_health_with_wither_shield =
+        health.saturating_add(calculate_wither_shield_health(crit_damage));
 
-    let next_effective_health_milestone_with_wither_shield =
+    let effective_health = calculate_effective_health(health, defense);
+    let effective_health_with_wither_shield =
+        calculate_effective_health(total_health_with_wither_shield, defense);
+
+    let (
+        next_effective_health_milestone,
+        next_effective_health_milestone_with_wither_shield,
+    ) = match (
+        calculate_next_effective_health_milestone(
+            effective_health,
+            enemy_damage_per_hit,
+        ),
         calculate_next_effective_health_milestone(
             effective_health_with_wither_shield,
             enemy_damage_per_hit,
-        );
+        ),
+    ) {
+        (Ok(milestone), Ok(milestone_with_wither_shield)) =>
+            (milestone, milestone_with_wither_shield),
+
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!(
+                "{}{e}",
+                "error: can't calculate the next Effective Health milestone: "
+                    .red()
+            );
+
+            return false;
+        },
+    };
 
     let difference = next_effective_health_milestone - effective_health;
     let difference_with_wither_shield =
@@ -110,238 +300,759 @@ pub(crate) fn survivability_calculator(
         "To afford to take another hit, you need {next_effective_health_milestone} ({difference} more than your current) Effective Health, or {next_effective_health_milestone_with_wither_shield} ({difference_with_wither_shield} more than your current) Effective Health with Wither Shield."
     );
 
-    let needed_health =
-        find_needed_health(health, defense, next_effective_health_milestone);
-    let needed_defense =
-        find_needed_defense(health, defense, next_effective_health_milestone);
+    let needed_health = solver_result_to_string(find_needed_health(
+        health,
+        defense,
+        next_effective_health_milestone,
+    ));
+    let needed_defense = solver_result_to_string(find_needed_defense(
+        health,
+        defense,
+        next_effective_health_milestone,
+    ));
 
     println!();
-    println!(
-        "To afford to take another hit, you need {needed_health} more Health or {needed_defense} more Defense."
-    );
 
-    let needed_health_with_wither_shield = find_needed_health(
-        total_health_with_wither_shield,
+    if enemy.ignores_defense {
+        println!(
+            "To afford to take another hit, you need {needed_health} more Health."
+        );
+    } else {
+        println!(
+            "To afford to take another hit, you need {needed_health} more Health or {needed_defense} more Defense."
+        );
+    }
+
+    let needed_health_with_wither_shield =
+        solver_result_to_string(find_needed_health(
+            total_health_with_wither_shield,
+            defense,
+            next_effective_health_milestone_with_wither_shield,
+        ));
+    let needed_crit_damage = solver_result_to_string(find_needed_crit_damage(
+        health,
+        crit_damage,
         defense,
         next_effective_health_milestone_with_wither_shield,
-    );
-
-    let needed_crit_damage = f64_to_i32(
-        (f64::from(find_needed_health(
+    ));
+    let needed_defense_with_wither_shield =
+        solver_result_to_string(find_needed_defense(
             total_health_with_wither_shield,
             defense,
             next_effective_health_milestone_with_wither_shield,
-        )) / 1.5)
-            .ceil(),
+        ));
+
+    if enemy.ignores_defense {
+        println!(
+            "To afford to take another hit with Wither Shield, you need {needed_health_with_wither_shield} more Health or {needed_crit_damage} more Crit Damage."
+        );
+    } else {
+        println!(
+            "To afford to take another hit with Wither Shield, you need {needed_health_with_wither_shield} more Health, {needed_crit_damage} more Crit Damage or {needed_defense_with_wither_shield} more Defense."
+        );
+    }
+
+    true
+}
+
+#[inline]
+fn time_to_death_simulator(
+    start_without_user_input: &mut Option<Instant>,
+) -> bool {
+    let Some(enemy) = select_enemy() else {
+        return false;
+    };
+
+    let enemy_hit_interval_seconds = ask_float_input(
+        "How often does your enemy hit you? (in seconds, e.g 0.5): ",
+        Some(1.0 / f64::from(TICKS_PER_SECOND)),
+        None,
+    );
+
+    let health = ask_int_input("What's your Health?: ", Some(100), None);
+    let defense = ask_defense(enemy);
+
+    let health_regen_per_second = ask_float_input(
+        "How much Health do you regenerate per second?: ",
+        Some(0.0),
+        None,
+    );
+    let healing_per_second = ask_float_input(
+        "How much healing per second do you get from other sources? (abilities, healers, etc. enter 0 if none): ",
+        Some(0.0),
+        None,
+    );
+    let healing_bonus_percent = ask_float_input(
+        "What's your bonus healing percentage? (from Mending, Vitality, etc. enter 0 if none): ",
+        Some(0.0),
+        None,
     );
 
-    let needed_defense_with_wither_shield = find_needed_defense(
-        total_health_with_wither_shield,
+    let crit_damage = ask_int_input(
+        "What's your Crit Damage? (If you don't plan to use Wither Shield, enter 0): ",
+        Some(0),
+        None,
+    );
+
+    let party = ask_party();
+
+    *start_without_user_input = Some(Instant::now());
+
+    let party_buffs = calculate_party_buffs(&party, health);
+
+    let parameters = SurvivalParameters {
+        health,
         defense,
-        next_effective_health_milestone_with_wither_shield,
+
+        enemy_damage_per_hit: enemy.damage_per_hit,
+        enemy_hit_interval_ticks: cmp::max(
+            1,
+            f64_to_i32(
+                (enemy_hit_interval_seconds * f64::from(TICKS_PER_SECOND))
+                    .round(),
+            ),
+        ),
+
+        health_regen_per_second,
+        healing_per_second: healing_per_second
+            + party_buffs.healing_per_second,
+        healing_bonus_percent,
+
+        wither_shield_absorption: calculate_wither_shield_health(crit_damage),
+
+        damage_reduction_percent: party_buffs.damage_reduction_percent,
+        absorption: party_buffs.absorption,
+    };
+
+    let result = simulate_survival(&parameters);
+
+    println!();
+    println!(
+        "{} The Wither Shield is assumed to be re-cast as soon as its cooldown ends, and the enemy is assumed to hit you as soon as the fight starts. Special variants/attacks and damage reduction other than Defense, Wither Shield and party buffs aren't factored in.",
+        "Note:".red()
     );
 
+    if !party.is_empty() {
+        print_party_buffs_note();
+    }
+    println!();
+
+    match result.ticks_survived {
+        Some(ticks) => println!(
+            "You will survive for {} seconds ({} hits).",
+            (f64::from(ticks) / f64::from(TICKS_PER_SECOND))
+                .to_string()
+                .bright_red(),
+            ticks / parameters.enemy_hit_interval_ticks + 1
+        ),
+
+        None => println!(
+            "You will survive for at least {} seconds.",
+            (MAXIMUM_SIMULATED_TICKS / TICKS_PER_SECOND)
+                .to_string()
+                .bright_green()
+        ),
+    }
+
     println!(
-        "To afford to take another hit with Wither Shield, you need {needed_health_with_wither_shield} more Health, {needed_crit_damage} more Crit Damage or {needed_defense_with_wither_shield} more Defense."
+        "Your enemy deals {} damage per second, and your healing can sustain up to {} damage per second.",
+        format!("{:.2}", result.incoming_damage_per_second).yellow(),
+        format!("{:.2}", result.sustainable_damage_per_second).yellow()
     );
 
+    if result.survives_indefinitely {
+        println!(
+            "{}",
+            "You can survive indefinitely at your current heal rate."
+                .bright_green()
+        );
+    } else {
+        println!(
+            "{}",
+            "You can't survive indefinitely at your current heal rate."
+                .bright_red()
+        );
+    }
+
+    true
+}
+
+#[inline]
+fn effective_health_grid_report(
+    start_without_user_input: &mut Option<Instant>,
+) -> bool {
+    let Some(enemy) = select_enemy() else {
+        return false;
+    };
+
+    let Some(healths) = ask_grid_axis("Health", 100) else {
+        return false;
+    };
+
+    let defenses = if enemy.ignores_defense {
+        println!(
+            "{} Your enemy deals true damage, which ignores Defense.",
+            "Note:".red()
+        );
+
+        vec![0]
+    } else {
+        let Some(defenses) = ask_grid_axis("Defense", 0) else {
+            return false;
+        };
+
+        defenses
+    };
+
+    let crit_damage = ask_int_input(
+        "What's your Crit Damage? (If you don't plan to use Wither Shield, enter 0): ",
+        Some(0),
+        None,
+    );
+    let csv_path = ask_string_input(
+        "Enter a file path to export the grid as CSV (leave empty to skip): ",
+    );
+
+    *start_without_user_input = Some(Instant::now());
+
+    let wither_shield_health = calculate_wither_shield_health(crit_damage);
+
+    let grid = calculate_hits_to_die_grid(
+        &healths,
+        &defenses,
+        0,
+        enemy.damage_per_hit,
+    );
+    let grid_with_wither_shield = calculate_hits_to_die_grid(
+        &healths,
+        &defenses,
+        wither_shield_health,
+        enemy.damage_per_hit,
+    );
+
+    println!();
+    println!(
+        "{} Values are hits to die, rounded down. Highlighted values are breakpoints, where you can afford to take another hit compared to the previous Health or Defense.",
+        "Note:".red()
+    );
+
+    println!();
+    println!("Without Wither Shield:");
+    print_hits_to_die_grid(&healths, &defenses, &grid);
+
+    println!();
+    println!(
+        "With Wither Shield ({} extra Health):",
+        with_comma_separators(&wither_shield_health.to_string())
+            .unwrap_or_else(|| wither_shield_health.to_string())
+    );
+    print_hits_to_die_grid(&healths, &defenses, &grid_with_wither_shield);
+
+    if csv_path.is_empty() {
+        return true;
+    }
+
+    let mut csv = String::from(
+        "wither_shield,health,defense,effective_health,hits_to_die,next_milestone_effective_health,breakpoint\n",
+    );
+
+    for (uses_wither_shield, grid) in
+        [(false, &grid), (true, &grid_with_wither_shield)]
+    {
+        write_hits_to_die_grid_csv(
+            &mut csv,
+            &healths,
+            &defenses,
+            grid,
+            uses_wither_shield,
+            if uses_wither_shield { wither_shield_health } else { 0 },
+            enemy.damage_per_hit,
+        );
+    }
+
+    if !write_file(Path::new(&csv_path), &csv) {
+        return false;
+    }
+
+    println!();
+    println!("Exported the grid to {csv_path}");
+
     true
 }
 
 #[inline]
 #[must_use]
-fn find_needed_health(hp: i32, defense: i32, to_effective_health: i32) -> i32 {
-    for new_hp in hp..=i32::MAX {
-        let ehp = calculate_effective_health(new_hp, defense);
+fn ask_grid_axis(stat: &str, minimum: i32) -> Option<Vec<i32>> {
+    let from = ask_int_input(
+        &format!("What's the lowest {stat} to show?: "),
+        Some(minimum),
+        None,
+    );
+    let to = ask_int_input(
+        &format!("What's the highest {stat} to show?: "),
+        Some(from),
+        None,
+    );
+    let step = ask_int_input(
+        &format!("How much {stat} should be between the rows?: "),
+        Some(1),
+        None,
+    );
 
-        if ehp >= to_effective_health {
-            return new_hp - hp;
-        }
+    // Checked before building the axis, so huge ranges are not allocated.
+    let length = calculate_grid_axis_length(from, to, step);
+
+    if length > MAXIMUM_GRID_AXIS_LENGTH {
+        eprintln!(
+            "{}{length} (maximum is {MAXIMUM_GRID_AXIS_LENGTH}), try using a bigger step",
+            format!("error: too many {stat} values to show: ").red()
+        );
+
+        return None;
     }
 
-    -1
+    Some(calculate_grid_axis(from, to, step))
 }
 
 #[inline]
 #[must_use]
-fn find_needed_defense(
-    hp: i32,
-    defense: i32,
-    to_effective_health: i32,
-) -> i32 {
-    for new_defense in defense..=i32::MAX {
-        let ehp = calculate_effective_health(hp, new_defense);
+pub(crate) fn calculate_grid_axis(from: i32, to: i32, step: i32) -> Vec<i32> {
+    (from..=to).step_by(usize::try_from(step).unwrap_or(1).max(1)).collect()
+}
 
-        if ehp >= to_effective_health {
-            return new_defense - defense;
-        }
+// The amount of values calculate_grid_axis returns, without building the
+// axis.
+#[inline]
+#[must_use]
+pub(crate) fn calculate_grid_axis_length(
+    from: i32,
+    to: i32,
+    step: i32,
+) -> usize {
+    if to < from {
+        return 0;
     }
 
-    -1
+    let length =
+        (i64::from(to) - i64::from(from)) / i64::from(step.max(1)) + 1;
+
+    usize::try_from(length).unwrap_or(usize::MAX)
 }
 
+// Rows are Health values and columns are Defense values, each cell is the
+// hits to die rounded down.
 #[inline]
 #[must_use]
-fn calculate_next_effective_health_milestone(
-    effective_health: i32,
+pub(crate) fn calculate_hits_to_die_grid(
+    healths: &[i32],
+    defenses: &[i32],
+    extra_health: i32,
     enemy_damage_per_hit: i32,
-) -> i32 {
-    let original_hits_to_die =
-        calculate_hits_to_die(effective_health, enemy_damage_per_hit);
-    let mut hits_to_die;
+) -> Vec<Vec<i32>> {
+    healths
+        .iter()
+        .map(|&health| {
+            defenses
+                .iter()
+                .map(|&defense| {
+                    f64_to_i32(
+                        calculate_hits_to_die(
+                            calculate_effective_health(
+                                health.saturating_add(extra_health),
+                                defense,
+                            ),
+                            enemy_damage_per_hit,
+                        )
+                        .trunc(),
+                    )
+                })
+                .collect()
+        })
+        .collect()
+}
 
-    for ehp in effective_health..=i32::MAX {
-        hits_to_die = calculate_hits_to_die(ehp, enemy_damage_per_hit);
+// A cell is a breakpoint if it takes more hits to die than the cell with the
+// previous Health or the previous Defense.
+#[inline]
+#[must_use]
+pub(crate) fn is_hits_to_die_breakpoint(
+    grid: &[Vec<i32>],
+    row: usize,
+    column: usize,
+) -> bool {
+    let cell = |row: usize, column: usize| {
+        grid.get(row).and_then(|cells| cells.get(column)).copied()
+    };
 
-        if !compare_f64(original_hits_to_die, original_hits_to_die.ceil())
-            && compare_f64(hits_to_die, original_hits_to_die.ceil())
-        {
-            return ehp;
-        }
+    let Some(hits_to_die) = cell(row, column) else {
+        return false;
+    };
 
-        if compare_f64(hits_to_die, original_hits_to_die + 1.0) {
-            return ehp;
-        }
+    let exceeds = |previous: Option<i32>| {
+        previous.is_some_and(|previous| hits_to_die > previous)
+    };
+
+    (row > 0 && exceeds(cell(row - 1, column)))
+        || (column > 0 && exceeds(cell(row, column - 1)))
+}
+
+#[inline]
+fn print_hits_to_die_grid(
+    healths: &[i32],
+    defenses: &[i32],
+    grid: &[Vec<i32>],
+) {
+    let mut header = format!("{:>GRID_CELL_WIDTH$}", "HP \\ Def");
+
+    for defense in defenses {
+        let _ = write!(header, "{defense:>GRID_CELL_WIDTH$}");
     }
 
-    0
+    println!("{}", header.bold());
+
+    for (row, (health, cells)) in healths.iter().zip(grid).enumerate() {
+        print!("{}", format!("{health:>GRID_CELL_WIDTH$}").bold());
+
+        for (column, hits_to_die) in cells.iter().enumerate() {
+            let cell = format!("{hits_to_die:>GRID_CELL_WIDTH$}");
+
+            if is_hits_to_die_breakpoint(grid, row, column) {
+                print!("{}", cell.bright_green());
+            } else {
+                print!("{cell}");
+            }
+        }
+
+        println!();
+    }
 }
 
 #[inline]
-#[must_use]
-fn calculate_hits_to_die(
-    effective_health: i32,
+fn write_hits_to_die_grid_csv(
+    csv: &mut String,
+    healths: &[i32],
+    defenses: &[i32],
+    grid: &[Vec<i32>],
+    uses_wither_shield: bool,
+    extra_health: i32,
     enemy_damage_per_hit: i32,
-) -> f64 {
-    1.0 + f64::max(
-        1.0,
-        f64::from(effective_health) / f64::from(enemy_damage_per_hit),
-    )
+) {
+    for (row, (&health, cells)) in healths.iter().zip(grid).enumerate() {
+        for (column, (&defense, hits_to_die)) in
+            defenses.iter().zip(cells).enumerate()
+        {
+            let effective_health = calculate_effective_health(
+                health.saturating_add(extra_health),
+                defense,
+            );
+            let next_milestone = calculate_next_effective_health_milestone(
+                effective_health,
+                enemy_damage_per_hit,
+            )
+            .map_or_else(|_| String::new(), |milestone| milestone.to_string());
+
+            let _ = writeln!(
+                csv,
+                "{uses_wither_shield},{health},{defense},{effective_health},{hits_to_die},{next_milestone},{}",
+                is_hits_to_die_breakpoint(grid, row, column)
+            );
+        }
+    }
 }
 
 #[inline]
 #[must_use]
-const fn calculate_effective_health(health: i32, defense: i32) -> i32 {
-    health * (1 + defense / 100)
+fn get_damage_taken_multiplier(health: i32, defense: i32) -> f64 {
+    let effective_health = calculate_effective_health(health, defense);
+
+    if effective_health <= 0 {
+        return 1.0;
+    }
+
+    f64::from(health) / f64::from(effective_health)
 }
 
 #[inline]
 #[must_use]
-fn get_enemy_damage_per_hit(selection: i32) -> i32 {
-    match selection {
-        1 | 2 => {
-            let start = if selection == 1 { 3 } else { 5 };
-
-            let mut total_index = 0;
-
-            for dungeon_type in 1..=2 {
-                let dungeon_type_as_str = if dungeon_type == 1 {
-                    "F"
-                } else {
-                    println!();
-
-                    "M"
-                };
-
-                for floor in start..=7 {
-                    total_index += 1;
-
-                    let as_str = floor.to_string();
-
-                    println!(
-                        " {}. {}{}",
-                        total_index.to_string().bright_blue(),
-                        dungeon_type_as_str,
-                        as_str
-                    );
-                }
+pub(crate) fn simulate_survival(
+    parameters: &SurvivalParameters,
+) -> SurvivalResult {
+    let max_health = f64::from(parameters.health);
+    let damage_taken_multiplier =
+        get_damage_taken_multiplier(parameters.health, parameters.defense);
+    let party_damage_multiplier =
+        1.0 - parameters.damage_reduction_percent / 100.0;
+    let damage_per_hit = f64::from(parameters.enemy_damage_per_hit)
+        * damage_taken_multiplier
+        * party_damage_multiplier;
+
+    let healing_multiplier = 1.0 + parameters.healing_bonus_percent / 100.0;
+    let healing_per_tick = (parameters.health_regen_per_second
+        + parameters.healing_per_second)
+        * healing_multiplier
+        / f64::from(TICKS_PER_SECOND);
+
+    let uses_wither_shield = parameters.wither_shield_absorption > 0;
+
+    let mut health = max_health;
+    let mut absorption = 0.0;
+    let mut party_absorption = f64::from(parameters.absorption);
+    let mut wither_shield_ticks_left = 0;
+
+    let mut ticks_survived = None;
+
+    for tick in 0..MAXIMUM_SIMULATED_TICKS {
+        if uses_wither_shield && tick % WITHER_SHIELD_COOLDOWN_TICKS == 0 {
+            absorption = f64::from(parameters.wither_shield_absorption);
+            wither_shield_ticks_left = WITHER_SHIELD_DURATION_TICKS;
+        }
+
+        if tick % parameters.enemy_hit_interval_ticks == 0 {
+            let mut damage = damage_per_hit;
+
+            if wither_shield_ticks_left > 0 {
+                damage *= 1.0 - WITHER_SHIELD_DAMAGE_REDUCTION_PERCENT / 100.0;
             }
 
-            let floor_selection =
-                ask_int_input("Select floor: ", Some(1), Some(total_index));
-
-            match (selection, floor_selection) {
-                (1, 1) => F3_SHADOW_ASSASSIN_DAMAGE,
-                (1, 2) => F4_SHADOW_ASSASSIN_DAMAGE,
-                (1, 3) => F5_SHADOW_ASSASSIN_DAMAGE,
-                (1, 4) => F6_SHADOW_ASSASSIN_DAMAGE,
-                (1, 5) => F7_SHADOW_ASSASSIN_DAMAGE,
-
-                (1, 6) => M3_SHADOW_ASSASSIN_DAMAGE,
-                (1, 7) => M4_SHADOW_ASSASSIN_DAMAGE,
-                (1, 8) => M5_SHADOW_ASSASSIN_DAMAGE,
-                (1, 9) => M6_SHADOW_ASSASSIN_DAMAGE,
-                (1, 10) => M7_SHADOW_ASSASSIN_DAMAGE,
-
-                (2, 1) => F5_FELS_DAMAGE,
-                (2, 2) => F6_FELS_DAMAGE,
-                (2, 3) => F7_FELS_DAMAGE,
-
-                (2, 4) => M5_FELS_DAMAGE,
-                (2, 5) => M6_FELS_DAMAGE,
-                (2, 6) => M7_FELS_DAMAGE,
-
-                (..) => {
-                    eprintln!(
-                        "{}({selection}, {floor_selection})",
-                        "error: invalid selections: ".red()
-                    );
-
-                    0
-                },
+            // Wither Shield's absorption is used up first, since it would be
+            // lost when it expires.
+            let absorbed = f64::min(absorption, damage);
+            let absorbed_by_party =
+                f64::min(party_absorption, damage - absorbed);
+
+            absorption -= absorbed;
+            party_absorption -= absorbed_by_party;
+            health -= damage - absorbed - absorbed_by_party;
+
+            if health <= 0.0 {
+                ticks_survived = Some(tick);
+
+                break;
             }
-        },
+        }
 
-        3 => {
-            println!();
-            println!("Select tier: ");
-            println!(" {}. Tier 1", "1".bright_blue(),);
-            println!(" {}. Tier 2", "2".bright_blue(),);
-            println!(" {}. Tier 3", "3".bright_blue(),);
-            println!(" {}. Tier 4", "4".bright_blue(),);
-
-            let tier_selection =
-                ask_int_input("Enter a number to select: ", Some(1), Some(4));
-
-            match tier_selection {
-                1 => VOIDGLOOM_SERAPH_TIER_1_TOTAL_DAMAGE,
-                2 => VOIDGLOOM_SERAPH_TIER_2_TOTAL_DAMAGE,
-                3 => {
-                    println!();
-                    println!(
-                        "{} This assumes there are no heads, and the hit shield DPS increase is also not factored in.",
-                        "Note:".red()
-                    );
-
-                    VOIDGLOOM_SERAPH_TIER_3_TOTAL_DAMAGE
-                },
-                4 => {
-                    println!();
-                    println!(
-                        "{} This assumes there are no heads, you didn't get hit by any lasers, and the hit shield DPS increase is also not factored in.",
-                        "Note:".red()
-                    );
-
-                    VOIDGLOOM_SERAPH_TIER_4_TOTAL_DAMAGE
-                },
-
-                _ => {
-                    eprintln!(
-                        "{}{tier_selection}",
-                        "error: invalid selection: ".red()
-                    );
-
-                    0
-                },
+        health = f64::min(max_health, health + healing_per_tick);
+
+        if wither_shield_ticks_left > 0 {
+            wither_shield_ticks_left -= 1;
+
+            if wither_shield_ticks_left == 0 {
+                health = f64::min(
+                    max_health,
+                    (absorption
+                        * (WITHER_SHIELD_HEALING_CONVERSION_PERCENT / 100.0))
+                        .mul_add(healing_multiplier, health),
+                );
+                absorption = 0.0;
             }
-        },
+        }
+    }
 
-        4 => ask_int_input("Enter your enemy's damage: ", Some(0), None),
+    let ticks_per_second = f64::from(TICKS_PER_SECOND);
+    let hits_per_second =
+        ticks_per_second / f64::from(parameters.enemy_hit_interval_ticks);
 
-        _ => {
-            eprintln!("{}{selection}", "error: invalid selection: ".red());
+    let wither_shield_uptime = if uses_wither_shield {
+        f64::from(WITHER_SHIELD_DURATION_TICKS)
+            / f64::from(WITHER_SHIELD_COOLDOWN_TICKS)
+    } else {
+        0.0
+    };
+    let average_damage_reduction = wither_shield_uptime
+        .mul_add(-(WITHER_SHIELD_DAMAGE_REDUCTION_PERCENT / 100.0), 1.0);
+    let wither_shield_absorption_per_second = if uses_wither_shield {
+        f64::from(parameters.wither_shield_absorption)
+            / (f64::from(WITHER_SHIELD_COOLDOWN_TICKS) / ticks_per_second)
+    } else {
+        0.0
+    };
 
-            0
-        },
+    let sustainable_damage_per_second = healing_per_tick
+        .mul_add(ticks_per_second, wither_shield_absorption_per_second)
+        / (damage_taken_multiplier
+            * average_damage_reduction
+            * party_damage_multiplier);
+    let incoming_damage_per_second =
+        f64::from(parameters.enemy_damage_per_hit) * hits_per_second;
+
+    SurvivalResult {
+        ticks_survived,
+
+        sustainable_damage_per_second,
+        incoming_damage_per_second,
+
+        survives_indefinitely: ticks_survived.is_none()
+            && incoming_damage_per_second <= sustainable_damage_per_second,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum SolverError {
+    // No amount of the stat can reach the target, i.e. having no Health when
+    // solving for Defense or the enemy dealing no damage.
+    Unreachable,
+    // The answer is too big to fit into an i32.
+    Overflow,
+}
+
+impl fmt::Display for SolverError {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Unreachable => "unreachable",
+            Self::Overflow => "too high to calculate",
+        })
+    }
+}
+
+#[inline]
+#[must_use]
+fn solver_result_to_string(result: Result<i32, SolverError>) -> String {
+    match result {
+        Ok(value) => with_comma_separators(&value.to_string())
+            .unwrap_or_else(|| value.to_string()),
+
+        Err(e) => format!("({e})"),
+    }
+}
+
+#[inline]
+fn i64_to_solver_result(value: i64) -> Result<i32, SolverError> {
+    i32::try_from(value).map_err(|_| SolverError::Overflow)
+}
+
+// Effective Health is Health multiplied by this, the division is intentionally
+// an integer division, see calculate_effective_health.
+#[inline]
+#[must_use]
+fn effective_health_multiplier(defense: i32) -> i64 {
+    1 + i64::from(defense) / 100
+}
+
+#[inline]
+pub(crate) fn find_needed_health(
+    hp: i32,
+    defense: i32,
+    to_effective_health: i32,
+) -> Result<i32, SolverError> {
+    let multiplier = effective_health_multiplier(defense);
+    let target = i64::from(to_effective_health);
+
+    if i64::from(hp) * multiplier >= target {
+        return Ok(0);
+    }
+
+    if multiplier <= 0 {
+        return Err(SolverError::Unreachable);
+    }
+
+    let new_hp = target.div_ceil(multiplier);
+
+    if new_hp > i64::from(i32::MAX) {
+        return Err(SolverError::Overflow);
+    }
+
+    i64_to_solver_result(new_hp - i64::from(hp))
+}
+
+#[inline]
+pub(crate) fn find_needed_defense(
+    hp: i32,
+    defense: i32,
+    to_effective_health: i32,
+) -> Result<i32, SolverError> {
+    let target = i64::from(to_effective_health);
+
+    if i64::from(hp) * effective_health_multiplier(defense) >= target {
+        return Ok(0);
+    }
+
+    if hp <= 0 {
+        return Err(SolverError::Unreachable);
+    }
+
+    let needed_multiplier = target.div_ceil(i64::from(hp));
+    let new_defense =
+        cmp::max(i64::from(defense), 100 * (needed_multiplier - 1));
+
+    if new_defense > i64::from(i32::MAX) {
+        return Err(SolverError::Overflow);
     }
+
+    i64_to_solver_result(new_defense - i64::from(defense))
+}
+
+// Crit Damage only gives Health while using Wither Shield, so the Health given
+// by it is added on top of the hp parameter.
+#[inline]
+pub(crate) fn find_needed_crit_damage(
+    hp: i32,
+    crit_damage: i32,
+    defense: i32,
+    to_effective_health: i32,
+) -> Result<i32, SolverError> {
+    let wither_shield_health = calculate_wither_shield_health(crit_damage);
+    let total_health =
+        hp.checked_add(wither_shield_health).ok_or(SolverError::Overflow)?;
+    let needed_health =
+        find_needed_health(total_health, defense, to_effective_health)?;
+
+    if needed_health == 0 {
+        return Ok(0);
+    }
+
+    // Wither Shield Health is Crit Damage * 1.5 rounded down, so the smallest
+    // Crit Damage giving at least the needed Health is the needed Health
+    // divided by 1.5, rounded up.
+    let new_wither_shield_health =
+        i64::from(wither_shield_health) + i64::from(needed_health);
+    let new_crit_damage = (2 * new_wither_shield_health).div_ceil(3);
+
+    i64_to_solver_result(new_crit_damage - i64::from(crit_damage))
+}
+
+#[inline]
+pub(crate) fn calculate_next_effective_health_milestone(
+    effective_health: i32,
+    enemy_damage_per_hit: i32,
+) -> Result<i32, SolverError> {
+    if enemy_damage_per_hit <= 0 {
+        return Err(SolverError::Unreachable);
+    }
+
+    let damage = i64::from(enemy_damage_per_hit);
+
+    // Hits to die only goes up when Effective Health reaches the next
+    // multiple of the enemy's damage, but it never goes below 2, which is
+    // reached at 1x of the enemy's damage.
+    let hits = cmp::max(i64::from(effective_health), damage) / damage;
+
+    i64_to_solver_result((hits + 1) * damage)
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn calculate_wither_shield_health(crit_damage: i32) -> i32 {
+    f64_to_i32((f64::from(crit_damage) * 1.5).trunc())
+}
+
+#[inline]
+#[must_use]
+pub(crate) fn calculate_hits_to_die(
+    effective_health: i32,
+    enemy_damage_per_hit: i32,
+) -> f64 {
+    1.0 + f64::max(
+        1.0,
+        f64::from(effective_health) / f64::from(enemy_damage_per_hit),
+    )
+}
+
+#[inline]
+#[must_use]
+pub(crate) const fn calculate_effective_health(
+    health: i32,
+    defense: i32,
+) -> i32 {
+    health.saturating_mul(1 + defense / 100)
 }
@@ -2,21 +2,124 @@
 #![allow(clippy::unreachable)]
 #![allow(clippy::panic)]
 
-use crate::master_skull_upgrade_helper::get_total_required_amount;
+use crate::arguments::Command;
+use crate::arguments::parse_arguments;
+use crate::catacombs_level_planner::calculate_runs_needed;
+use crate::catacombs_level_planner::calculate_xp_per_run;
+use crate::catacombs_level_planner::get_catacombs_level;
+use crate::catacombs_level_planner::get_catacombs_level_with_progress;
+use crate::catacombs_level_planner::get_catacombs_xp_for_level;
+use crate::catacombs_stat_boost_calculator::DungeonSetup;
+use crate::catacombs_stat_boost_calculator::calculate_dungeon_stat;
+use crate::catacombs_stat_boost_calculator::get_generals_medallion_stat_boost;
+use crate::catacombs_stat_boost_calculator::get_total_dungeon_stat_boost;
+use crate::class_average_planner::calculate_class_average;
+use crate::class_average_planner::plan_class_runs;
+use crate::damage_calculator::DamageParameters;
+use crate::damage_calculator::calculate_damage;
+use crate::damage_calculator::calculate_hits_to_kill;
+use crate::damage_calculator::find_needed_stat_to_one_shot;
+use crate::dungeon_chest_simulator::PricedChest;
+use crate::dungeon_chest_simulator::floor_chests;
+use crate::dungeon_chest_simulator::simulate_chest_openings;
+use crate::dungeon_score_calculator::DungeonRun;
+use crate::dungeon_score_calculator::calculate_bonus_score;
+use crate::dungeon_score_calculator::calculate_dungeon_score;
+use crate::dungeon_score_calculator::calculate_required_secrets;
+use crate::dungeon_score_calculator::calculate_skill_score;
+use crate::dungeon_score_calculator::calculate_speed_score;
+use crate::dungeon_score_calculator::get_score_rank;
+use crate::enemy_database::SelectedEnemy;
+use crate::enemy_database::enemies;
+use crate::enemy_database::search_enemies;
+use crate::fees::FeeRates;
+use crate::item_data::find_item_id;
+use crate::loadout_optimizer::LoadoutGoal;
+use crate::loadout_optimizer::Stats;
+use crate::loadout_optimizer::optimize_loadouts;
+use crate::loadout_optimizer::parse_loadout_candidates;
+use std::collections::BTreeMap;
 use std::env;
 use std::path::Path;
 use std::path::PathBuf;
 
+use crate::party_buffs::DungeonClass;
+use crate::party_buffs::PartyBuffs;
+use crate::party_buffs::PartyMember;
+use crate::party_buffs::calculate_effective_health_with_party_buffs;
+use crate::party_buffs::calculate_member_buffs;
+use crate::party_buffs::calculate_party_buffs;
+use crate::price_cache::PriceCache;
+use crate::price_cache::cached_ids_by_category;
+use crate::price_cache::format_age;
+use crate::price_cache::insert_cached;
+use crate::price_history::MAXIMUM_HISTORY_WINDOW_SECONDS;
+use crate::price_history::Market;
+use crate::price_history::PriceSummary;
+use crate::price_history::bazaar_records;
+use crate::price_history::lowest_bin_records;
+use crate::price_history::parse_price_history;
+use crate::price_history::parse_window;
+use crate::price_history::prices_in_window;
+use crate::price_history::prune_price_history;
+use crate::price_history::sparkline;
+use crate::price_history::summarize_prices;
+use crate::price_providers::PriceProvider;
+use crate::price_providers::Provider;
+use crate::price_providers::extract_bazaar_product;
+use crate::price_providers::extract_slothpixel_listings;
+use crate::price_providers::parse_static_prices;
+use crate::price_watcher::WatchState;
+use crate::price_watcher::check_price;
+use crate::price_watcher::get_poll_interval_seconds;
+use crate::price_watcher::parse_watch_config;
+use crate::prices::BazaarProduct;
+use crate::prices::BazaarSide;
+use crate::prices::BinListings;
+use crate::profile_importer::extract_member_profile;
+use crate::profile_importer::find_master_skull_tier;
+use crate::profile_importer::find_profile_members;
 use crate::rng_simulator;
 use crate::rng_simulator::drop_rate_with_magic_find_and_looting;
 use crate::rng_simulator::get_minimum_magic_find_needed_to_succeed;
 use crate::rng_simulator::passes;
+use crate::skill_average_helper::Skill;
+use crate::skill_average_helper::apply_level_ups;
+use crate::skill_average_helper::calculate_fractional_skill_average;
+use crate::skill_average_helper::get_skill_level;
+use crate::skill_average_helper::get_skill_level_with_progress;
+use crate::skill_average_helper::get_skill_xp_for_fractional_level;
+use crate::skill_average_helper::get_skill_xp_for_level;
+use crate::skill_average_helper::plan_cheapest_level_ups;
+use crate::skill_average_helper::plan_least_xp_for_target_average;
+use crate::star_cost_calculator::calculate_star_upgrade_cost;
+use crate::survivability_calculator::SolverError;
+use crate::survivability_calculator::SurvivalParameters;
+use crate::survivability_calculator::calculate_effective_health;
+use crate::survivability_calculator::calculate_grid_axis;
+use crate::survivability_calculator::calculate_grid_axis_length;
+use crate::survivability_calculator::calculate_hits_to_die;
+use crate::survivability_calculator::calculate_hits_to_die_grid;
+use crate::survivability_calculator::calculate_next_effective_health_milestone;
+use crate::survivability_calculator::calculate_wither_shield_health;
+use crate::survivability_calculator::find_needed_crit_damage;
+use crate::survivability_calculator::find_needed_defense;
+use crate::survivability_calculator::find_needed_health;
+use crate::survivability_calculator::is_hits_to_die_breakpoint;
+use crate::survivability_calculator::simulate_survival;
+use crate::tiered_upgrade_planner::CombinePlan;
+use crate::tiered_upgrade_planner::CombineRecipe;
+use crate::tiered_upgrade_planner::get_total_required_amount;
+use crate::tiered_upgrade_planner::plan_cheapest_upgrade;
 use crate::utils::FunctionResult;
 use crate::utils::cap;
 use crate::utils::compare_f64;
 use crate::utils::conditional_value_or_default;
 use crate::utils::convert_i32_option_to_f64_option;
 use crate::utils::f64_to_i32;
+use crate::utils::format_coins;
+use crate::utils::format_coins_f64;
+use crate::utils::fuzzy_match_score;
 use crate::utils::get_odds;
 use crate::utils::has_unique_elements;
 use crate::utils::i64_to_f64;
@@ -125,6 +228,12 @@ fn with_comma_separators_works() {
     assert_eq!(with_comma_separators("100000").unwrap(), "100,000");
 }
 
+#[test]
+fn format_coins_works() {
+    assert_eq!(format_coins(-1_234_567), "-1,234,567");
+    assert_eq!(format_coins_f64(999_999.5), "1,000,000");
+}
+
 #[test]
 fn percentage_change_works() {
     let value = percentage_change(1.0, 2.0);
@@ -314,7 +423,1215 @@ fn get_minimum_magic_find_needed_to_succeed_works() {
 
 #[test]
 fn get_total_required_amount_works() {
-    assert_eq!(get_total_required_amount(1, 7), 4096);
+    assert_eq!(get_total_required_amount(4, 1, 7), 4096);
+}
+
+#[test]
+fn tiered_upgrade_planner_works() {
+    let recipe =
+        CombineRecipe::with_tiered_id("Test", "TEST_{tier}", 3, 2, 10);
+    let listings = [vec![10, 10, 10, 100], vec![50], vec![500]]
+        .map(|prices| BinListings { total: Some(prices.len()), prices });
+
+    assert_eq!(recipe.ids, ["TEST_1", "TEST_2", "TEST_3"]);
+    assert_eq!(CombineRecipe::master_skull().ids[6], "MASTER_SKULL_TIER_7");
+
+    // The second Tier 2 is cheaper to buy than to combine from the 100 coin
+    // Tier 1, and both are cheaper to combine than buying Tier 3.
+    assert_eq!(
+        plan_cheapest_upgrade(&recipe, &listings, &[0, 0, 0], 3),
+        Some(CombinePlan {
+            used: vec![0, 0, 0],
+            bought: vec![2, 1, 0],
+            combines: vec![2, 3],
+            combine_fees: 20,
+            total_cost: 90,
+        })
+    );
+    assert_eq!(
+        plan_cheapest_upgrade(&recipe, &listings, &[1, 0, 0], 3),
+        Some(CombinePlan {
+            used: vec![1, 0, 0],
+            bought: vec![3, 0, 0],
+            combines: vec![2, 2, 3],
+            combine_fees: 30,
+            total_cost: 60,
+        })
+    );
+    assert_eq!(
+        plan_cheapest_upgrade(&recipe, &listings, &[0, 1, 0], 3),
+        Some(CombinePlan {
+            used: vec![0, 1, 0],
+            bought: vec![2, 0, 0],
+            combines: vec![2, 3],
+            combine_fees: 20,
+            total_cost: 40,
+        })
+    );
+    // Owned items are used before buying any.
+    assert_eq!(
+        plan_cheapest_upgrade(&recipe, &listings, &[4, 0, 0], 3)
+            .unwrap()
+            .total_cost,
+        30
+    );
+    assert_eq!(
+        plan_cheapest_upgrade(&recipe, &listings, &[0, 0, 1], 3),
+        Some(CombinePlan {
+            used: vec![0; 3],
+            bought: vec![0; 3],
+            ..CombinePlan::default()
+        })
+    );
+    assert_eq!(plan_cheapest_upgrade(&recipe, &[], &[0, 0, 0], 3), None);
+
+    // The work only grows with the listings, not with the tiers and ratio.
+    let recipe =
+        CombineRecipe::with_tiered_id("Test", "TEST_{tier}", 20, 4, 10);
+    let mut listings = vec![BinListings::default(); 20];
+
+    listings[0] = BinListings { total: Some(1000), prices: vec![1; 1000] };
+    listings[19] = BinListings { total: Some(1), prices: vec![1_000_000] };
+
+    assert_eq!(
+        plan_cheapest_upgrade(&recipe, &listings, &[0; 20], 20)
+            .map(|plan| plan.bought),
+        Some([vec![0; 19], vec![1]].concat())
+    );
+}
+
+const fn survival_parameters(
+    health_regen_per_second: f64,
+    wither_shield_absorption: i32,
+) -> SurvivalParameters {
+    SurvivalParameters {
+        health: 1000,
+        defense: 0,
+
+        enemy_damage_per_hit: 250,
+        enemy_hit_interval_ticks: 20,
+
+        health_regen_per_second,
+        healing_per_second: 0.0,
+        healing_bonus_percent: 0.0,
+
+        wither_shield_absorption,
+
+        damage_reduction_percent: 0.0,
+        absorption: 0,
+    }
+}
+
+#[test]
+fn simulate_survival_without_healing() {
+    let result = simulate_survival(&survival_parameters(0.0, 0));
+
+    // Hits land on ticks 0, 20, 40 and 60, the fourth one is fatal.
+    assert_eq!(result.ticks_survived, Some(60));
+    assert!(!result.survives_indefinitely);
+}
+
+#[test]
+fn simulate_survival_with_enough_healing() {
+    let result = simulate_survival(&survival_parameters(300.0, 0));
+
+    assert_eq!(result.ticks_survived, None);
+    assert!(result.survives_indefinitely);
+    assert!(
+        result.sustainable_damage_per_second
+            >= result.incoming_damage_per_second
+    );
+}
+
+#[test]
+fn simulate_survival_with_wither_shield() {
+    let without_wither_shield =
+        simulate_survival(&survival_parameters(0.0, 0));
+    let with_wither_shield = simulate_survival(&survival_parameters(0.0, 500));
+
+    assert!(
+        with_wither_shield.ticks_survived.unwrap()
+            > without_wither_shield.ticks_survived.unwrap()
+    );
+}
+
+#[test]
+fn simulate_survival_with_party_buffs() {
+    let with_absorption = simulate_survival(&SurvivalParameters {
+        absorption: 250,
+        ..survival_parameters(0.0, 0)
+    });
+    let with_damage_reduction = simulate_survival(&SurvivalParameters {
+        damage_reduction_percent: 50.0,
+        ..survival_parameters(0.0, 0)
+    });
+
+    assert_eq!(with_absorption.ticks_survived, Some(80));
+    assert_eq!(with_damage_reduction.ticks_survived, Some(140));
+}
+
+#[test]
+fn party_buffs_work() {
+    let healer = calculate_member_buffs(
+        PartyMember { class: DungeonClass::Healer, class_level: 50 },
+        1000,
+    );
+
+    assert!(compare_f64(healer.healing_per_second, 30.0));
+    assert_eq!(healer.absorption, 200);
+
+    let tank = calculate_member_buffs(
+        PartyMember { class: DungeonClass::Tank, class_level: 25 },
+        1000,
+    );
+
+    assert!(compare_f64(tank.damage_reduction_percent, 7.5));
+    assert_eq!(tank.absorption, 50);
+
+    assert_eq!(
+        calculate_member_buffs(
+            PartyMember { class: DungeonClass::Mage, class_level: 50 },
+            1000
+        ),
+        PartyBuffs::default()
+    );
+
+    let tanks = calculate_party_buffs(
+        &[PartyMember { class: DungeonClass::Tank, class_level: 50 }; 2],
+        1000,
+    );
+
+    assert!(compare_f64(tanks.damage_reduction_percent, 27.75));
+    assert_eq!(tanks.absorption, 200);
+
+    assert_eq!(
+        calculate_effective_health_with_party_buffs(1000, 0, healer),
+        1200
+    );
+    assert_eq!(
+        calculate_effective_health_with_party_buffs(
+            1000,
+            0,
+            PartyBuffs { damage_reduction_percent: 50.0, ..healer }
+        ),
+        2400
+    );
+}
+
+// Brute-force versions of the Effective Health solvers, which the closed-form
+// ones must agree with.
+fn brute_force_needed_health(hp: i32, defense: i32, to: i32) -> i32 {
+    (hp..=i32::MAX)
+        .find(|&new_hp| calculate_effective_health(new_hp, defense) >= to)
+        .unwrap()
+        - hp
+}
+
+fn brute_force_needed_defense(hp: i32, defense: i32, to: i32) -> i32 {
+    (defense..=i32::MAX)
+        .find(|&new_defense| calculate_effective_health(hp, new_defense) >= to)
+        .unwrap()
+        - defense
+}
+
+fn brute_force_needed_crit_damage(
+    hp: i32,
+    crit_damage: i32,
+    defense: i32,
+    to: i32,
+) -> i32 {
+    (crit_damage..=i32::MAX)
+        .find(|&new_crit_damage| {
+            calculate_effective_health(
+                hp + calculate_wither_shield_health(new_crit_damage),
+                defense,
+            ) >= to
+        })
+        .unwrap()
+        - crit_damage
+}
+
+fn brute_force_next_effective_health_milestone(
+    effective_health: i32,
+    enemy_damage_per_hit: i32,
+) -> i32 {
+    let original_hits_to_die =
+        calculate_hits_to_die(effective_health, enemy_damage_per_hit);
+
+    (effective_health..=i32::MAX)
+        .find(|&ehp| {
+            let hits_to_die = calculate_hits_to_die(ehp, enemy_damage_per_hit);
+
+            (!compare_f64(original_hits_to_die, original_hits_to_die.ceil())
+                && compare_f64(hits_to_die, original_hits_to_die.ceil()))
+                || compare_f64(hits_to_die, original_hits_to_die + 1.0)
+        })
+        .unwrap()
+}
+
+#[test]
+fn effective_health_solvers_agree_with_brute_force() {
+    for hp in [100, 257, 1000, 1337, 5000] {
+        for defense in [0, 50, 99, 100, 250, 1234] {
+            for crit_damage in [0, 1, 77, 250] {
+                for enemy_damage_per_hit in [100, 777, 5000, 48000] {
+                    let effective_health =
+                        calculate_effective_health(hp, defense);
+                    let milestone = calculate_next_effective_health_milestone(
+                        effective_health,
+                        enemy_damage_per_hit,
+                    )
+                    .unwrap();
+
+                    assert_eq!(
+                        milestone,
+                        brute_force_next_effective_health_milestone(
+                            effective_health,
+                            enemy_damage_per_hit
+                        )
+                    );
+
+                    assert_eq!(
+                        find_needed_health(hp, defense, milestone).unwrap(),
+                        brute_force_needed_health(hp, defense, milestone)
+                    );
+                    assert_eq!(
+                        find_needed_defense(hp, defense, milestone).unwrap(),
+                        brute_force_needed_defense(hp, defense, milestone)
+                    );
+                    assert_eq!(
+                        find_needed_crit_damage(
+                            hp,
+                            crit_damage,
+                            defense,
+                            milestone
+                        )
+                        .unwrap(),
+                        brute_force_needed_crit_damage(
+                            hp,
+                            crit_damage,
+                            defense,
+                            milestone
+                        )
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn effective_health_solvers_report_errors() {
+    assert_eq!(
+        calculate_next_effective_health_milestone(1000, 0),
+        Err(SolverError::Unreachable)
+    );
+    assert_eq!(find_needed_defense(0, 0, 1000), Err(SolverError::Unreachable));
+    assert_eq!(find_needed_health(i32::MAX, 0, i32::MAX), Ok(0));
+    assert_eq!(
+        calculate_next_effective_health_milestone(i32::MAX, i32::MAX >> 1),
+        Err(SolverError::Overflow)
+    );
+    assert_eq!(
+        find_needed_crit_damage(i32::MAX, 100, 0, i32::MAX),
+        Err(SolverError::Overflow)
+    );
+}
+
+#[test]
+fn fuzzy_match_score_works() {
+    assert!(fuzzy_match_score("fels", "Dungeons Fels (F7)").is_some());
+    assert!(fuzzy_match_score("vdglm", "Slayer Voidgloom Seraph").is_some());
+    assert!(fuzzy_match_score("kuudra", "Slayer Voidgloom Seraph").is_none());
+
+    assert!(
+        fuzzy_match_score("seraph", "Slayer Voidgloom Seraph")
+            > fuzzy_match_score("srph", "Slayer Voidgloom Seraph")
+    );
+}
+
+#[test]
+fn enemy_database_is_valid() {
+    let enemies = enemies().unwrap();
+
+    assert_ne!(enemies, []);
+
+    for enemy in enemies {
+        assert!(!enemy.attacks.is_empty(), "{} has no attacks", enemy.name);
+        assert!(
+            enemy.health.is_none_or(|health| health > 0),
+            "{} has no Health",
+            enemy.name
+        );
+        // The damage calculator is mostly used against these.
+        assert!(
+            !(enemy.name.starts_with("Shadow Assassin")
+                || enemy.name.starts_with("Fels"))
+                || enemy.health.is_some(),
+            "{} has no Health",
+            enemy.name
+        );
+    }
+}
+
+#[test]
+fn search_enemies_works() {
+    let enemies = enemies().unwrap();
+
+    assert_eq!(search_enemies(enemies, "").len(), enemies.len());
+    assert_eq!(search_enemies(enemies, "f7 fels")[0].name, "Fels (F7)");
+    assert_eq!(
+        search_enemies(enemies, "seraph 4")[0].name,
+        "Voidgloom Seraph (Tier 4)"
+    );
+}
+
+#[test]
+fn hits_to_die_grid_works() {
+    assert_eq!(calculate_grid_axis(100, 300, 100), [100, 200, 300]);
+    assert_eq!(calculate_grid_axis(0, 250, 100), [0, 100, 200]);
+    assert_eq!(calculate_grid_axis_length(0, 250, 100), 3);
+    assert_eq!(calculate_grid_axis_length(5, 5, 1), 1);
+    assert_eq!(calculate_grid_axis_length(0, i32::MAX, 1), 1 << 31);
+
+    let grid =
+        calculate_hits_to_die_grid(&[1000, 2000], &[0, 50, 100], 0, 1000);
+
+    assert_eq!(grid, [[2, 2, 3], [3, 3, 5]]);
+
+    assert!(!is_hits_to_die_breakpoint(&grid, 0, 0));
+    assert!(!is_hits_to_die_breakpoint(&grid, 0, 1));
+    assert!(is_hits_to_die_breakpoint(&grid, 0, 2));
+    assert!(is_hits_to_die_breakpoint(&grid, 1, 0));
+    assert!(is_hits_to_die_breakpoint(&grid, 1, 1));
+    assert!(is_hits_to_die_breakpoint(&grid, 1, 2));
+
+    assert_eq!(calculate_hits_to_die_grid(&[1000], &[0], 1000, 1000), [[3]]);
+}
+
+#[test]
+fn dungeon_item_stats_work() {
+    assert_eq!(get_generals_medallion_stat_boost(0), 1);
+    assert_eq!(get_generals_medallion_stat_boost(999), 3);
+    assert_eq!(get_generals_medallion_stat_boost(i32::MAX), 6);
+
+    let stat_boost = get_total_dungeon_stat_boost(DungeonSetup {
+        catacombs_level: 0,
+        normal_stars: 5,
+        master_stars: 2,
+        secrets: 10_000,
+    });
+
+    // 10% from Catacombs level 0, 60% from stars and 5% from the medallion.
+    assert_eq!(stat_boost, 75);
+    assert!(compare_f64(calculate_dungeon_stat(100, stat_boost), 175.0));
+    assert!(compare_f64(calculate_dungeon_stat(-20, stat_boost), -35.0));
+}
+
+#[test]
+fn catacombs_level_planner_works() {
+    assert_eq!(get_catacombs_xp_for_level(0), Some(0));
+    assert_eq!(get_catacombs_xp_for_level(2), Some(125));
+    assert_eq!(get_catacombs_xp_for_level(50), Some(569_809_640));
+    assert_eq!(get_catacombs_xp_for_level(51), Some(769_809_640));
+
+    assert_eq!(get_catacombs_level(0), 0);
+    assert_eq!(get_catacombs_level(124), 1);
+    assert_eq!(get_catacombs_level(125), 2);
+    assert_eq!(get_catacombs_level(569_809_640), 50);
+    assert_eq!(get_catacombs_level(769_809_640), 51);
+
+    for level in 0..=60 {
+        assert_eq!(
+            get_catacombs_level(get_catacombs_xp_for_level(level).unwrap()),
+            level
+        );
+    }
+
+    assert!(compare_f64(calculate_xp_per_run(300_000, 300, 10.0), 330_000.0));
+    assert!(compare_f64(calculate_xp_per_run(300_000, 150, 0.0), 150_000.0));
+
+    assert_eq!(calculate_runs_needed(1_000_000, 330_000.0), Some(4));
+    assert_eq!(calculate_runs_needed(0, 330_000.0), Some(0));
+    assert_eq!(calculate_runs_needed(1_000_000, 0.0), None);
+}
+
+#[test]
+fn class_average_planner_works() {
+    assert!(compare_f64(get_catacombs_level_with_progress(0, 50), 0.0));
+    assert!(compare_f64(get_catacombs_level_with_progress(25, 50), 0.5));
+    assert!(compare_f64(get_catacombs_level_with_progress(125, 50), 2.0));
+    assert!(compare_f64(
+        get_catacombs_level_with_progress(i64::from(i32::MAX), 50),
+        50.0
+    ));
+
+    let level_2_xp = 125.0;
+
+    assert!(compare_f64(calculate_class_average(&[level_2_xp; 5]), 2.0));
+    assert!(compare_f64(
+        calculate_class_average(&[level_2_xp, 0.0, 0.0, 0.0, 0.0]),
+        0.4
+    ));
+
+    let plan = plan_class_runs([0.0; 5], 100.0, 2.0).unwrap();
+
+    assert!(calculate_class_average(&plan.xp) >= 2.0);
+    // Spreading the runs is better than playing only one class.
+    assert_eq!(plan.runs_per_class, [0, 1, 1, 1, 1]);
+
+    assert_eq!(
+        plan_class_runs([level_2_xp; 5], 100.0, 2.0).unwrap().total_runs(),
+        0
+    );
+    assert_eq!(plan_class_runs([0.0; 5], 0.0, 2.0), None);
+
+    // Far away targets are planned without going one run at a time.
+    let plan = plan_class_runs([0.0; 5], 100_000.0, 50.0).unwrap();
+
+    assert!(compare_f64(calculate_class_average(&plan.xp), 50.0));
+    assert!(plan.total_runs() < 20_000);
+}
+
+#[test]
+fn star_upgrade_cost_works() {
+    let cost =
+        calculate_star_upgrade_cost(&[100, 200, 300], 1_000.0, &[5.0, 10.0]);
+
+    assert_eq!(cost.essence, 600);
+    assert!(compare_f64(cost.essence_cost, 600_000.0));
+    assert!(compare_f64(cost.master_stars_cost, 15.0));
+    assert!(compare_f64(cost.total(), 600_015.0));
+
+    assert!(compare_f64(
+        calculate_star_upgrade_cost(&[], 0.0, &[]).total(),
+        0.0
+    ));
+}
+
+#[test]
+fn dungeon_chest_simulator_works() {
+    let wood = PricedChest {
+        name: "Wood".to_owned(),
+        cost: 0.0,
+        loot: vec![(100.0, 1_000.0)],
+    };
+    let bedrock = PricedChest {
+        name: "Bedrock".to_owned(),
+        cost: 500_000.0,
+        loot: vec![(100.0, 100_000.0), (10.0, 10_000_000.0)],
+    };
+
+    assert!(compare_f64(wood.expected_profit(), 1_000.0));
+    assert!(compare_f64(bedrock.expected_profit(), 600_000.0));
+
+    let chests = [wood, bedrock];
+    let mut rng = rng_simulator::new_rng();
+
+    // A Kismet Feather costing more than any chest is never worth it.
+    let simulation =
+        simulate_chest_openings(&chests, 100_000_000.0, 10_000, &mut rng);
+
+    assert!(!simulation.kismet_is_worth_it());
+    assert!(compare_f64(simulation.reroll_percent, 0.0));
+    assert_eq!(simulation.opened_percent.len(), 2);
+    assert!(
+        (simulation.opened_percent.iter().sum::<f64>() - 100.0).abs() < 0.001
+    );
+
+    // The Bedrock chest is only worth opening when it has the rare drop, so
+    // the profit per run is around 10% * 9,600,000 + 90% * 1,000.
+    assert!((simulation.profit_per_run - 960_900.0).abs() < 100_000.0);
+
+    // A free Kismet Feather is always worth using when the rare drop is
+    // missing.
+    let simulation = simulate_chest_openings(&chests, 0.0, 10_000, &mut rng);
+
+    assert!(simulation.kismet_is_worth_it());
+    assert!((simulation.reroll_percent - 90.0).abs() < 3.0);
+
+    assert!(floor_chests().is_some_and(|floors| {
+        floors.iter().all(|floor| !floor.chests.is_empty())
+    }));
+}
+
+#[test]
+fn dungeon_score_calculator_works() {
+    let run = DungeonRun {
+        deaths: 1,
+        puzzles_failed: 0,
+
+        secrets_found: 20,
+        total_secrets: 50,
+        secret_percent_required: 100,
+
+        crypts: 7,
+        has_mimic: true,
+        mimic_killed: true,
+        paul: false,
+
+        run_seconds: 300,
+        time_limit_seconds: 480,
+    };
+
+    let score = calculate_dungeon_score(run);
+
+    assert_eq!(score.skill, 98);
+    assert_eq!(score.exploration, 76);
+    assert_eq!(score.speed, 100);
+    assert_eq!(score.bonus, 7);
+    assert_eq!(get_score_rank(score.total()), "S");
+    // Floors without a Mimic never count its bonus.
+    assert_eq!(
+        calculate_bonus_score(DungeonRun { has_mimic: false, ..run }),
+        5
+    );
+
+    // 30% over the time limit loses 10 points for the first 20% and 2.5
+    // points for the next 10%.
+    assert_eq!(calculate_speed_score(624, 480), 87);
+    assert_eq!(calculate_speed_score(100_000, 480), 0);
+
+    assert_eq!(
+        calculate_required_secrets(DungeonRun {
+            total_secrets: 33,
+            secret_percent_required: 30,
+            ..run
+        }),
+        10
+    );
+    assert_eq!(calculate_skill_score(DungeonRun { deaths: 100, ..run }), 20);
+
+    assert_eq!(get_score_rank(300), "S+");
+    assert_eq!(get_score_rank(299), "S");
+    assert_eq!(get_score_rank(0), "D");
+}
+
+#[test]
+fn skill_xp_works() {
+    assert_eq!(get_skill_xp_for_level(0), 0);
+    assert_eq!(get_skill_xp_for_level(1), 50);
+    assert_eq!(get_skill_xp_for_level(50), 55_172_425);
+    assert_eq!(get_skill_xp_for_level(60), 111_672_425);
+
+    assert_eq!(get_skill_level(49, 60), 0);
+    assert_eq!(get_skill_level(50, 60), 1);
+    assert_eq!(get_skill_level(111_672_425, 60), 60);
+    assert_eq!(get_skill_level(111_672_425, 50), 50);
+
+    assert!(compare_f64(get_skill_level_with_progress(175 + 100, 60), 2.5));
+    assert!(compare_f64(
+        get_skill_level_with_progress(i64::MAX / 2, 50),
+        50.0
+    ));
+    assert_eq!(get_skill_xp_for_fractional_level(1.5), 50 + 125 / 2);
+    assert_eq!(get_skill_xp_for_fractional_level(60.0), 111_672_425);
+
+    let skills = Skill::ALL_SKILLS
+        .iter()
+        .map(|&skill| skill.with_xp(get_skill_xp_for_level(10)))
+        .collect::<Vec<_>>();
+
+    assert!(compare_f64(calculate_fractional_skill_average(&skills), 10.0));
+
+    // Reaching an average of 10.5 needs 5 more levels, each costing the XP of
+    // level 11, unless a skill is already part way there.
+    let mut skills_with_progress = skills.clone();
+    skills_with_progress[3] =
+        skills_with_progress[3].with_xp(get_skill_xp_for_level(10) + 4_000);
+
+    let Some(target_skills) =
+        plan_least_xp_for_target_average(&skills_with_progress, 10.5)
+    else {
+        panic!("target Skill Average should be reachable");
+    };
+
+    let total_xp = target_skills
+        .iter()
+        .zip(&skills_with_progress)
+        .map(|(target_skill, skill)| target_skill.xp - skill.xp)
+        .sum::<i64>();
+
+    assert_eq!(target_skills[3].level, 11);
+    assert_eq!(total_xp, 4 * 5_000 + 1_000);
+    assert_eq!(target_skills.iter().map(|skill| skill.level).sum::<i32>(), 95);
+
+    assert!(plan_least_xp_for_target_average(&skills, 61.0).is_none());
+}
+
+#[test]
+fn skill_leveling_plan_works() {
+    let skills = Skill::ALL_SKILLS
+        .iter()
+        .map(|&skill| skill.with_xp(get_skill_xp_for_level(20)))
+        .collect::<Vec<_>>();
+
+    // Only Alchemy (index 6) and Enchanting (index 5) can be bought, and
+    // Alchemy is cheaper until its levels need 3 times as much XP.
+    let mut coins_per_xp = vec![0.0; skills.len()];
+    coins_per_xp[5] = 3.0;
+    coins_per_xp[6] = 1.0;
+
+    let Some(level_ups) =
+        plan_cheapest_level_ups(&skills, 21.0, &coins_per_xp)
+    else {
+        panic!("target Skill Average should be reachable");
+    };
+
+    assert_eq!(level_ups.len(), 9);
+    assert!(
+        level_ups
+            .windows(2)
+            .all(|level_ups| level_ups[0].cost <= level_ups[1].cost)
+    );
+
+    let target_skills = apply_level_ups(&skills, &level_ups);
+
+    // Level 21 needs 300K XP and every level after needs 100K more, so 3
+    // times the XP of level 21 (900K) costs the same as level 27.
+    assert_eq!(target_skills[6].level, 28);
+    assert_eq!(target_skills[5].level, 21);
+    assert_eq!(target_skills[0].level, 20);
+    assert!(compare_f64(
+        level_ups.iter().map(|level_up| level_up.cost).sum(),
+        i64_to_f64(get_skill_xp_for_level(28) - get_skill_xp_for_level(20))
+            + 3.0 * 300_000.0
+    ));
+
+    // Skills without a rate are never leveled.
+    assert!(plan_cheapest_level_ups(&skills, 50.0, &coins_per_xp).is_none());
+}
+
+#[test]
+fn profile_importer_works() {
+    use base64::Engine as _;
+    use std::io::Write as _;
+
+    let arguments =
+        ["--profile", "profile.json", "--member", "abc"].map(str::to_owned);
+
+    let Some(parsed) = parse_arguments(&arguments).unwrap().profile else {
+        panic!("profile arguments should be valid");
+    };
+
+    assert_eq!(parsed.source, "profile.json");
+    assert_eq!(parsed.member.as_deref(), Some("abc"));
+    assert!(parse_arguments(&arguments[2..]).is_none());
+    assert!(parse_arguments(&arguments[..1]).is_none());
+    assert!(parse_arguments(&["--unknown".to_owned()]).is_none());
+
+    // Only the NBT text matters, so a plain string is gzipped instead of a
+    // real NBT compound.
+    let mut encoder = flate2::write::GzEncoder::new(
+        Vec::new(),
+        flate2::Compression::default(),
+    );
+    encoder
+        .write_all(b"id:MASTER_SKULL_TIER_4,id:MASTER_SKULL_TIER_6")
+        .unwrap();
+    let item_data = base64::engine::general_purpose::STANDARD
+        .encode(encoder.finish().unwrap());
+
+    assert_eq!(find_master_skull_tier(&item_data), Some(6));
+    assert_eq!(find_master_skull_tier("not base64"), None);
+
+    let json = serde_json::json!({
+        "profiles": [
+            { "selected": false, "members": { "other": {} } },
+            {
+                "selected": true,
+                "members": {
+                    "uuid": {
+                        "player_data": {
+                            "experience": {
+                                "SKILL_FARMING": 55_172_425.5,
+                                "SKILL_MINING": 1_000.0,
+                            },
+                        },
+                        "dungeons": {
+                            "secrets": 12_345,
+                            "dungeon_types": {
+                                "catacombs": { "experience": 569_809_640.0 },
+                            },
+                            "player_classes": {
+                                "mage": { "experience": 1_000.0 },
+                            },
+                        },
+                        "inventory": {
+                            "bag_contents": {
+                                "talisman_bag": { "data": item_data },
+                            },
+                        },
+                    },
+                },
+            },
+        ],
+    });
+
+    let members = find_profile_members(&json).unwrap();
+
+    assert_eq!(members.len(), 1);
+    assert_eq!(members[0].0, "uuid");
+
+    let profile = extract_member_profile(members[0].1);
+
+    assert_eq!(profile.get_skill_xp("Farming"), Some(55_172_425));
+    assert_eq!(profile.get_skill_xp("mining"), Some(1_000));
+    assert_eq!(profile.get_skill_xp("combat"), None);
+    assert_eq!(profile.catacombs_xp, Some(569_809_640));
+    assert_eq!(profile.secrets, Some(12_345));
+    assert_eq!(profile.master_skull_tier, Some(6));
+
+    let Some(class_xp) = profile.class_xp else {
+        panic!("class XP should be imported");
+    };
+
+    assert!(class_xp.contains(&1_000.0));
+    assert!(compare_f64(class_xp.iter().sum(), 1_000.0));
+
+    // The older flat profile format.
+    let profile = extract_member_profile(&serde_json::json!({
+        "experience_skill_combat": 123.0,
+    }));
+
+    assert_eq!(profile.get_skill_xp("combat"), Some(123));
+    assert_eq!(profile.class_xp, None);
+    assert_eq!(profile.master_skull_tier, None);
+
+    assert!(find_profile_members(&serde_json::json!({})).is_err());
+}
+
+#[test]
+fn price_providers_work() {
+    let arguments =
+        ["--price-provider", "hypixel", "--price-api-url"].map(str::to_owned);
+
+    assert!(parse_arguments(&arguments).is_none());
+    assert!(parse_arguments(&arguments[..1]).is_none());
+
+    let provider = |arguments: &[&str]| {
+        let arguments = arguments
+            .iter()
+            .map(|&argument| argument.to_owned())
+            .collect::<Vec<_>>();
+
+        parse_arguments(&arguments).unwrap().price_provider
+    };
+
+    assert_eq!(provider(&[]), None);
+    assert_eq!(
+        provider(&["--price-provider", "hypixel"]).unwrap().name(),
+        "Hypixel"
+    );
+    assert_eq!(
+        provider(&["--price-api-url", "http://localhost:8080/"]),
+        Provider::from_name("slothpixel", Some("http://localhost:8080"))
+    );
+    assert!(
+        parse_arguments(&["--price-provider", "unknown"].map(str::to_owned))
+            .is_none()
+    );
+    assert_eq!(Provider::defaults().len(), 3);
+
+    let prices = parse_static_prices(
+        Path::new("prices.json"),
+        r#"{"lowest_bin": {"MASTER_SKULL_TIER_1": 100000}, "bazaar": {"KISMET_FEATHER": {"buy": 1000000.0}}}"#,
+    )
+    .unwrap();
+
+    assert_eq!(prices.lowest_bin.get("MASTER_SKULL_TIER_1"), Some(&100_000));
+    assert_eq!(prices.bazaar["KISMET_FEATHER"].buy, Some(1_000_000.0));
+    assert_eq!(prices.bazaar["KISMET_FEATHER"].sell, None);
+    assert!(parse_static_prices(Path::new("prices.json"), "{").is_none());
+
+    // An item no one is selling is not an error, but an error body is.
+    assert_eq!(
+        extract_slothpixel_listings(&serde_json::json!({
+            "matching_query": 0,
+            "auctions": [],
+        })),
+        Some(BinListings { prices: vec![], total: Some(0) })
+    );
+    assert_eq!(
+        extract_slothpixel_listings(
+            &serde_json::json!({ "error": "Too many requests" })
+        ),
+        None
+    );
+
+    // String tag "id" with the 2 byte length of the value before it.
+    let mut nbt = b"\x0a\x00\x0fExtraAttributes".to_vec();
+    nbt.extend_from_slice(&[8, 0, 2, b'i', b'd', 0, 19]);
+    nbt.extend_from_slice(b"MASTER_SKULL_TIER_7\x00");
+
+    assert_eq!(find_item_id(&nbt).as_deref(), Some("MASTER_SKULL_TIER_7"));
+    assert_eq!(find_item_id(&nbt[..nbt.len() - 5]), None);
+    assert_eq!(find_item_id(b"no id"), None);
+}
+
+#[test]
+fn bazaar_products_work() {
+    let json = serde_json::json!({
+        "quick_status": {
+            "buyPrice": 10.5,
+            "sellPrice": 9.0,
+            "buyVolume": 1000,
+            "sellVolume": 0,
+            "buyMovingWeek": 50000,
+            "sellMovingWeek": 40000,
+        },
+        "buy_summary": [{ "pricePerUnit": 10.2, "amount": 64 }],
+        "sell_summary": [],
+    });
+
+    let Some(product) = extract_bazaar_product(&json) else {
+        panic!("product should be extracted");
+    };
+
+    assert_eq!(
+        product,
+        BazaarProduct {
+            buy: Some(10.5),
+            sell: Some(9.0),
+            buy_order: None,
+            sell_offer: Some(10.2),
+
+            buy_volume: Some(1000),
+            sell_volume: Some(0),
+            buy_moving_week: Some(50_000),
+            sell_moving_week: Some(40_000),
+        }
+    );
+
+    assert_eq!(product.price(BazaarSide::Buy), Some(10.5));
+    assert_eq!(product.price(BazaarSide::SellOffer), Some(10.2));
+    // Without buy orders, the instant sell price is used.
+    assert_eq!(product.price(BazaarSide::BuyOrder), Some(9.0));
+    assert_eq!(BazaarProduct::default().price(BazaarSide::Sell), None);
+    assert_eq!(extract_bazaar_product(&serde_json::json!({})), None);
+
+    let arguments = ["prices", "bazaar", "essence_wither"].map(str::to_owned);
+
+    assert_eq!(
+        parse_arguments(&arguments).unwrap().command,
+        Some(Command::BazaarProducts(vec!["ESSENCE_WITHER".to_owned()]))
+    );
+    assert!(parse_arguments(&arguments[..2]).is_none());
+}
+
+#[test]
+fn price_cache_works() {
+    let ids = ["KISMET_FEATHER", "RECOMBOBULATOR_3000"].map(str::to_owned);
+    let product = |buy| {
+        Some(BazaarProduct { buy: Some(buy), ..BazaarProduct::default() })
+    };
+
+    let mut cache = PriceCache::default();
+
+    insert_cached(
+        cache.bazaar_mut(),
+        &ids,
+        None,
+        vec![product(1_000_000.0), None],
+        100,
+    );
+    insert_cached(
+        cache.bazaar_mut(),
+        &ids[..1],
+        None,
+        vec![product(900_000.0)],
+        200,
+    );
+
+    let Some(cached) = cache.bazaar().get("KISMET_FEATHER") else {
+        panic!("price should be cached");
+    };
+
+    assert_eq!(cached.value, product(900_000.0));
+    assert_eq!(cached.age(500), 300);
+    assert_eq!(cached.age(0), 0);
+    assert_eq!(cache.bazaar()["RECOMBOBULATOR_3000"].value, None);
+    assert_eq!(cache.lowest_bin().get("KISMET_FEATHER"), None);
+    assert_eq!(
+        cached_ids_by_category(cache.bazaar()),
+        BTreeMap::from([(None, ids.to_vec())])
+    );
+
+    insert_cached(
+        cache.bin_listings_mut(),
+        &ids[..1],
+        None,
+        vec![BinListings { prices: vec![1, 2], total: Some(5) }],
+        100,
+    );
+
+    assert_eq!(
+        cached_ids_by_category(cache.bin_listings()),
+        BTreeMap::from([(None, ids[..1].to_vec())])
+    );
+
+    // Items fetched with a category are refreshed with the same category.
+    insert_cached(cache.lowest_bin_mut(), &ids[..1], None, vec![Some(1)], 100);
+    insert_cached(
+        cache.lowest_bin_mut(),
+        &ids[1..],
+        Some("misc"),
+        vec![Some(2)],
+        100,
+    );
+
+    assert_eq!(
+        cached_ids_by_category(cache.lowest_bin()),
+        BTreeMap::from([
+            (None, ids[..1].to_vec()),
+            (Some("misc".to_owned()), ids[1..].to_vec()),
+        ])
+    );
+
+    let json = serde_json::to_string(&cache).unwrap();
+
+    assert_eq!(serde_json::from_str::<PriceCache>(&json).unwrap(), cache);
+    assert_eq!(
+        serde_json::from_str::<PriceCache>("{}").unwrap(),
+        PriceCache::default()
+    );
+
+    assert_eq!(format_age(59), "59 seconds");
+    assert_eq!(format_age(3_599), "59 minutes");
+    assert_eq!(format_age(7_200), "2 hours");
+    assert_eq!(format_age(172_800), "2 days");
+
+    let arguments =
+        ["--offline", "--price-cache-ttl", "60", "prices", "refresh"]
+            .map(str::to_owned);
+    let parsed = parse_arguments(&arguments).unwrap();
+
+    assert!(parsed.price_cache.offline);
+    assert_eq!(parsed.price_cache.ttl_seconds, 60);
+    assert_eq!(parsed.command, Some(Command::RefreshPrices));
+    assert!(!parse_arguments(&[]).unwrap().price_cache.offline);
+    assert!(parse_arguments(&arguments[1..3]).is_some());
+    assert!(parse_arguments(&arguments[3..4]).is_none());
+    assert!(
+        parse_arguments(&["--price-cache-ttl", "-1"].map(str::to_owned))
+            .is_none()
+    );
+}
+
+#[test]
+fn fees_work() {
+    let rates = FeeRates::default();
+
+    assert!(compare_f64(rates.bin_sale_fees(500_000.0), 5_000.0));
+    // The claim tax only applies over 1M.
+    assert!(compare_f64(rates.bin_sale_fees(2_000_000.0), 40_000.0));
+    assert!(compare_f64(rates.bin_sale_fees(10_000_000.0), 300_000.0));
+    assert!(compare_f64(rates.bin_sale_fees(100_000_000.0), 3_500_000.0));
+    assert!(compare_f64(rates.bazaar_sale_fees(1_000.0), 12.5));
+
+    let arguments = [
+        "--bin-listing-fees",
+        "0,1,2",
+        "--bazaar-tax",
+        "1.125",
+        "prices",
+        "resell",
+        "kismet_feather",
+        "MASTER_SKULL_TIER_7",
+    ]
+    .map(str::to_owned);
+    let parsed = parse_arguments(&arguments).unwrap();
+
+    assert_eq!(
+        parsed.fee_rates,
+        FeeRates {
+            bin_listing_fee_percents: [0.0, 1.0, 2.0],
+            bazaar_tax_percent: 1.125,
+            ..rates
+        }
+    );
+    assert_eq!(
+        parsed.command,
+        Some(Command::ResellPrices(
+            ["KISMET_FEATHER", "MASTER_SKULL_TIER_7"]
+                .map(str::to_owned)
+                .to_vec()
+        ))
+    );
+    assert_eq!(parse_arguments(&[]).unwrap().fee_rates, rates);
+    assert!(parse_arguments(&arguments[..6]).is_none());
+    assert!(
+        parse_arguments(&["--bin-listing-fees", "1,2"].map(str::to_owned))
+            .is_none()
+    );
+    assert!(
+        parse_arguments(&["--bazaar-tax", "101"].map(str::to_owned)).is_none()
+    );
+}
+
+#[test]
+fn bin_listings_work() {
+    let listings =
+        BinListings { prices: vec![100, 120, 150, 400], total: Some(6) };
+
+    assert_eq!(listings.cost_to_buy(0), Some((0, 0)));
+    assert_eq!(listings.cost_to_buy(1), Some((100, 100)));
+    assert_eq!(listings.cost_to_buy(3), Some((370, 150)));
+    assert_eq!(listings.cost_to_buy(4), Some((770, 400)));
+    // Only the fetched listings can be bought.
+    assert_eq!(listings.cost_to_buy(5), None);
+    assert_eq!(BinListings::default().cost_to_buy(1), None);
+
+    let prices = parse_static_prices(
+        Path::new("prices.json"),
+        r#"{"lowest_bin": {"A": 5}, "bin_listings": {"B": [3, 1, 2]}}"#,
+    )
+    .unwrap();
+
+    assert_eq!(prices.bin_listings["B"], [3, 1, 2]);
+    assert!(!prices.bin_listings.contains_key("A"));
+}
+
+#[test]
+fn damage_calculator_works() {
+    let parameters = DamageParameters {
+        weapon_damage: 100,
+        strength: 100,
+        crit_damage: 100,
+        crit_chance: 50,
+
+        additive_multiplier_percent: 0.0,
+        multiplicative_multiplier: 1.0,
+
+        dungeon_stat_boost_percent: 0,
+        dungeon_item_strength: 0,
+        dungeon_item_crit_damage: 0,
+        dungeon_weapon: false,
+    };
+
+    let damage = calculate_damage(parameters);
+
+    assert!(compare_f64(damage.non_crit, 210.0));
+    assert!(compare_f64(damage.crit, 420.0));
+    assert!(compare_f64(damage.expected, 315.0));
+
+    let boosted_damage = calculate_damage(DamageParameters {
+        dungeon_stat_boost_percent: 100,
+        dungeon_item_strength: 100,
+        dungeon_item_crit_damage: 100,
+        dungeon_weapon: true,
+        ..parameters
+    });
+
+    assert!(compare_f64(boosted_damage.crit, 1845.0));
+
+    // Only the stats of dungeon items are boosted.
+    let weapon_boosted_damage = calculate_damage(DamageParameters {
+        dungeon_stat_boost_percent: 100,
+        dungeon_weapon: true,
+        ..parameters
+    });
+
+    assert!(compare_f64(weapon_boosted_damage.crit, 820.0));
+
+    assert_eq!(calculate_hits_to_kill(1000, 420.0), Some(3));
+    assert_eq!(calculate_hits_to_kill(840, 420.0), Some(2));
+    assert_eq!(calculate_hits_to_kill(1000, 0.0), None);
+
+    assert_eq!(
+        find_needed_stat_to_one_shot(parameters, 1000, |parameters| {
+            &mut parameters.strength
+        }),
+        Some(277)
+    );
+    assert_eq!(
+        find_needed_stat_to_one_shot(parameters, 1000, |parameters| {
+            &mut parameters.crit_damage
+        }),
+        Some(277)
+    );
+    assert_eq!(
+        find_needed_stat_to_one_shot(parameters, 1000, |parameters| {
+            &mut parameters.weapon_damage
+        }),
+        Some(145)
+    );
+    assert_eq!(
+        find_needed_stat_to_one_shot(parameters, 100, |parameters| {
+            &mut parameters.strength
+        }),
+        Some(0)
+    );
+    assert_eq!(
+        find_needed_stat_to_one_shot(
+            DamageParameters { multiplicative_multiplier: 0.0, ..parameters },
+            1000,
+            |parameters| &mut parameters.strength
+        ),
+        None
+    );
+}
+
+#[test]
+fn loadout_optimizer_works() {
+    let candidates = parse_loadout_candidates(
+        "slot,name,health,defense,crit_damage\n\
+         # Helmets\n\
+         helmet,A,100,50,0\n\
+         helmet,B,200,0,0\n\
+         \n\
+         chestplate,C,300,100,0\n\
+         chestplate,D,0,0,0\n",
+    )
+    .unwrap();
+
+    assert_eq!(candidates.len(), 4);
+
+    let base_stats = Stats { health: 1000, defense: 0, crit_damage: 0 };
+    let enemy = SelectedEnemy { damage_per_hit: 1000, ignores_defense: false };
+    let names = |goal| {
+        optimize_loadouts(base_stats, &candidates, goal, enemy, false)
+            .iter()
+            .map(|loadout| {
+                loadout
+                    .items
+                    .iter()
+                    .map(|item| item.name.as_str())
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        names(LoadoutGoal::MaximumEffectiveHealth),
+        ["BC", "AC", "BD", "AD"]
+    );
+    assert_eq!(names(LoadoutGoal::SurviveHits(2)), ["C", "BC", "AC"]);
+    assert_eq!(names(LoadoutGoal::SurviveHits(3)), ["BC"]);
+    assert!(names(LoadoutGoal::SurviveHits(4)).is_empty());
+
+    // Defense is useless against true damage.
+    let candidates =
+        parse_loadout_candidates("helmet,E,0,200,0\nhelmet,F,100,0,0")
+            .unwrap();
+    let best = |ignores_defense| {
+        optimize_loadouts(
+            base_stats,
+            &candidates,
+            LoadoutGoal::MaximumEffectiveHealth,
+            SelectedEnemy { ignores_defense, ..enemy },
+            false,
+        )[0]
+        .items[0]
+            .name
+            .clone()
+    };
+
+    assert_eq!(best(false), "E");
+    assert_eq!(best(true), "F");
+
+    assert!(parse_loadout_candidates("helmet,A,100,50").is_err());
+    assert!(parse_loadout_candidates("helmet,A,100,fifty,0").is_err());
 }
 
 #[test]
@@ -363,3 +1680,133 @@ fn test_return_first_elem_if_only_elem0(
         },
     }
 }
+
+#[test]
+fn price_watcher_works() {
+    let config = parse_watch_config(
+        r#"{
+            "poll_interval_seconds": 10,
+            "items": [
+                { "id": "hyperion", "below": 900, "change_percent": 10.0 },
+                { "id": "KISMET_FEATHER" }
+            ]
+        }"#,
+    )
+    .unwrap();
+
+    assert_eq!(config.items[0].id, "HYPERION");
+    assert_eq!(config.items[1].below, None);
+    // Polling every 10 seconds would hit the rate limits.
+    assert_eq!(get_poll_interval_seconds(&config, 2), 60);
+    assert_eq!(get_poll_interval_seconds(&config, 300), 300);
+    assert_eq!(
+        Provider::from_name("hypixel", None)
+            .unwrap()
+            .get_poll_interval_seconds(2),
+        300
+    );
+    assert_eq!(
+        Provider::from_name("coflnet", None)
+            .unwrap()
+            .get_poll_interval_seconds(2),
+        2
+    );
+    assert!(parse_watch_config("{}").is_none());
+
+    let item = &config.items[0];
+    let mut state = WatchState::default();
+
+    assert!(check_price(item, &mut state, 1000).is_empty());
+    assert!(check_price(item, &mut state, 950).is_empty());
+    // Crossing the threshold and moving 15% from 1000 alerts both.
+    assert_eq!(check_price(item, &mut state, 850).len(), 2);
+    // Still below the threshold and within 10% of the new reference.
+    assert!(check_price(item, &mut state, 800).is_empty());
+    assert_eq!(check_price(item, &mut state, 1000).len(), 1);
+    assert_eq!(check_price(item, &mut state, 899).len(), 2);
+
+    let arguments = ["prices", "watch", "watchlist.json"].map(str::to_owned);
+
+    assert_eq!(
+        parse_arguments(&arguments).unwrap().command,
+        Some(Command::WatchPrices(Some(PathBuf::from("watchlist.json"))))
+    );
+    assert_eq!(
+        parse_arguments(&arguments[..2]).unwrap().command,
+        Some(Command::WatchPrices(None))
+    );
+}
+
+#[test]
+fn price_history_works() {
+    let ids = ["HYPERION", "KISMET_FEATHER"].map(str::to_owned);
+    let product =
+        BazaarProduct { buy: Some(10.5), ..BazaarProduct::default() };
+
+    let mut records = lowest_bin_records(&ids, &[Some(900), None], 100);
+
+    records.extend(lowest_bin_records(&ids[..1], &[Some(1100)], 200));
+    records.extend(lowest_bin_records(&ids[..1], &[Some(1000)], 300));
+    // Only the sides with a price are recorded.
+    records.extend(bazaar_records(&ids[1..], &[Some(product)], 300));
+
+    assert_eq!(records.len(), 4);
+    assert_eq!(records[3].market, Market::BazaarBuy);
+
+    let jsonl = records
+        .iter()
+        .map(|record| serde_json::to_string(record).unwrap())
+        .collect::<Vec<_>>()
+        .join("\n");
+    // A cut off line is skipped.
+    let history = parse_price_history(&format!("{jsonl}\n{{\"id\": \"HYP"));
+
+    assert_eq!(history, records);
+    assert_eq!(
+        prices_in_window(&history, "HYPERION", Market::LowestBin, 150, 300),
+        vec![1100.0, 1000.0]
+    );
+    // Only the records older than the maximum window are dropped.
+    assert_eq!(
+        prune_price_history(
+            history.clone(),
+            MAXIMUM_HISTORY_WINDOW_SECONDS + 200
+        )
+        .len(),
+        3
+    );
+    assert_eq!(
+        summarize_prices(&[900.0, 1100.0, 1000.0]),
+        Some(PriceSummary { min: 900.0, median: 1000.0, max: 1100.0 })
+    );
+    assert_eq!(summarize_prices(&[1.0, 2.0]).unwrap().median, 1.5);
+    assert_eq!(summarize_prices(&[]), None);
+
+    assert_eq!(sparkline(&[1.0, 8.0, 4.5], 10), "▁█▅");
+    // Neighbouring prices are averaged to fit the width.
+    assert_eq!(sparkline(&[1.0, 1.0, 8.0, 8.0], 2), "▁█");
+    assert_eq!(sparkline(&[5.0, 5.0], 10), "▄▄");
+
+    assert_eq!(parse_window("30m"), Some(1800));
+    assert_eq!(parse_window("7d"), Some(604_800));
+    assert_eq!(parse_window("0h"), None);
+    assert_eq!(parse_window("7"), None);
+    assert_eq!(parse_window("30d"), Some(MAXIMUM_HISTORY_WINDOW_SECONDS));
+    assert_eq!(parse_window("31d"), None);
+
+    let arguments =
+        ["--smooth-prices", "1d", "prices", "history", "hyperion", "12h"]
+            .map(str::to_owned);
+    let parsed = parse_arguments(&arguments).unwrap();
+
+    assert_eq!(parsed.price_smoothing_window, Some(86400));
+    assert_eq!(
+        parsed.command,
+        Some(Command::PriceHistory("HYPERION".to_owned(), 43200))
+    );
+    assert!(parse_arguments(&arguments[2..4]).is_none());
+    assert!(
+        parse_arguments(&["--smooth-prices", "soon"].map(str::to_owned))
+            .is_none()
+    );
+}
@@ -0,0 +1,602 @@
+use core::iter;
+use std::time::Instant;
+
+use colored::Colorize;
+
+use crate::fees::fee_rates;
+use crate::fees::print_resell_price;
+use crate::price_cache::format_age;
+use crate::price_history::price_smoothing_window;
+use crate::price_history::smooth_lowest_bin_prices;
+use crate::prices::BinListings;
+use crate::prices::fetch_bin_listings;
+use crate::utils::ask_int_input;
+use crate::utils::ask_string_input;
+use crate::utils::format_coins;
+use crate::utils::i64_to_f64;
+
+const TIER_PLACEHOLDER: &str = "{tier}";
+
+// Listings fetched of each tier at most, enough to make a Tier 7 Master
+// Skull from Tier 1 ones. Every 100 listings are a request to the price
+// APIs.
+const MAXIMUM_LISTING_DEPTH: usize = 4096;
+
+// An item that is upgraded by combining ratio items of a tier into one item
+// of the next tier.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CombineRecipe {
+    pub(crate) name: String,
+    // Item ID of each tier, the lowest tier first.
+    pub(crate) ids: Vec<String>,
+    pub(crate) ratio: usize,
+    // Coins paid for each combine, i.e. to the anvil or an NPC.
+    pub(crate) combine_fee: i64,
+    // Narrows down the auction search if given.
+    pub(crate) category: Option<String>,
+}
+
+impl CombineRecipe {
+    // The ID has the placeholder {tier} in place of the tier number, i.e.
+    // MASTER_SKULL_TIER_{tier}.
+    #[inline]
+    #[must_use]
+    pub(crate) fn with_tiered_id(
+        name: &str,
+        id: &str,
+        tiers: usize,
+        ratio: usize,
+        combine_fee: i64,
+    ) -> Self {
+        Self {
+            name: name.to_owned(),
+            ids: (1..=tiers)
+                .map(|tier| id.replace(TIER_PLACEHOLDER, &tier.to_string()))
+                .collect(),
+            ratio,
+            combine_fee,
+            category: None,
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub(crate) fn master_skull() -> Self {
+        Self {
+            category: Some("accessories".to_owned()),
+
+            ..Self::with_tiered_id(
+                "Master Skull",
+                "MASTER_SKULL_TIER_{tier}",
+                7,
+                4,
+                0,
+            )
+        }
+    }
+
+    #[inline]
+    #[must_use]
+    pub(crate) const fn tiers(&self) -> usize {
+        self.ids.len()
+    }
+}
+
+#[inline]
+#[must_use]
+fn presets() -> Vec<CombineRecipe> {
+    vec![CombineRecipe::master_skull()]
+}
+
+// What to buy and combine to get to the target tier.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CombinePlan {
+    // Owned items used of each tier.
+    pub(crate) used: Vec<usize>,
+    // Items bought of each tier, from the cheapest listings.
+    pub(crate) bought: Vec<usize>,
+    // The tier made by each combine, in the order they can be done.
+    pub(crate) combines: Vec<usize>,
+    pub(crate) combine_fees: i64,
+    // Including the combine fees.
+    pub(crate) total_cost: i64,
+}
+
+impl CombinePlan {
+    #[inline]
+    #[must_use]
+    fn new(tiers: usize) -> Self {
+        Self {
+            used: vec![0; tiers],
+            bought: vec![0; tiers],
+            ..Self::default()
+        }
+    }
+}
+
+// Where an item of a tier comes from in a plan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Source {
+    Owned,
+    Bought,
+    Combined,
+}
+
+// The amount of the starting tier that combines into one of the ending tier.
+#[inline]
+#[must_use]
+pub(crate) fn get_total_required_amount(
+    ratio: usize,
+    starting_tier: usize,
+    ending_tier: usize,
+) -> usize {
+    let mut total_required_amount = 1_usize;
+
+    for _ in starting_tier..ending_tier {
+        total_required_amount = total_required_amount.saturating_mul(ratio);
+    }
+
+    total_required_amount
+}
+
+// Finds the cheapest mix of owned items to use and tiers to buy to get one
+// item of the target tier, walking the listings of each tier from the
+// cheapest. Owned is the amount of items owned of each tier. Returns None if
+// there are not enough listings.
+#[inline]
+#[must_use]
+pub(crate) fn plan_cheapest_upgrade(
+    recipe: &CombineRecipe,
+    listings: &[BinListings],
+    owned: &[usize],
+    target_tier: usize,
+) -> Option<CombinePlan> {
+    let mut plan = CombinePlan::new(recipe.tiers());
+
+    if owned
+        .iter()
+        .skip(target_tier.saturating_sub(1))
+        .any(|&amount| amount > 0)
+    {
+        return Some(plan);
+    }
+
+    if recipe.ratio == 0 {
+        return None;
+    }
+
+    let items_of_tiers =
+        get_cheapest_items_of_tiers(recipe, listings, owned, target_tier);
+
+    // Walks down from the target tier, taking as many of the cheapest items
+    // of each tier as the tier above combines.
+    let mut needed = 1;
+    let mut combines_of_tiers = vec![0; target_tier];
+
+    for (index, items) in items_of_tiers.iter().enumerate().rev() {
+        let mut combines = 0;
+
+        for &(price, source) in items.get(..needed)? {
+            match source {
+                Source::Owned => plan.used[index] += 1,
+
+                Source::Bought => {
+                    plan.bought[index] += 1;
+                    plan.total_cost += price;
+                },
+
+                Source::Combined => combines += 1,
+            }
+        }
+
+        combines_of_tiers[index] = combines;
+        needed = combines * recipe.ratio;
+    }
+
+    // Lower tiers are combined first, since the higher tiers need them.
+    for (tier, &combines) in (1..).zip(&combines_of_tiers) {
+        let fees = recipe.combine_fee * i64::try_from(combines).ok()?;
+
+        plan.combines.extend(iter::repeat_n(tier, combines));
+        plan.combine_fees += fees;
+        plan.total_cost += fees;
+    }
+
+    Some(plan)
+}
+
+// The items of each tier up to the target that can be obtained and what each
+// costs, the cheapest first. Owned items come first since they are free,
+// then the cheaper of buying the next listing or combining the next cheapest
+// items of the tier below. Each tier only keeps as many as one item of the
+// target tier needs, so the work is bounded by the listings.
+#[inline]
+#[must_use]
+fn get_cheapest_items_of_tiers(
+    recipe: &CombineRecipe,
+    listings: &[BinListings],
+    owned: &[usize],
+    target_tier: usize,
+) -> Vec<Vec<(i64, Source)>> {
+    let mut items_of_tiers: Vec<Vec<(i64, Source)>> =
+        Vec::with_capacity(target_tier);
+
+    for tier in 1..=target_tier {
+        let index = tier - 1;
+
+        let owned_items = iter::repeat_n(
+            (0, Source::Owned),
+            owned.get(index).copied().unwrap_or(0),
+        );
+        let mut bought_items = listings
+            .get(index)
+            .map_or(&[][..], |listings| &listings.prices)
+            .iter()
+            .map(|&price| (price, Source::Bought))
+            .peekable();
+        let mut combined_items = items_of_tiers
+            .last()
+            .map_or(&[][..], Vec::as_slice)
+            .chunks_exact(recipe.ratio)
+            .map(|items| {
+                let cost = items
+                    .iter()
+                    .fold(recipe.combine_fee, |cost, &(price, _)| {
+                        cost.saturating_add(price)
+                    });
+
+                (cost, Source::Combined)
+            })
+            .peekable();
+
+        // Both are sorted, so merging them keeps the items sorted. Buying is
+        // preferred when it costs the same, since it needs no combining.
+        let merged = iter::from_fn(|| {
+            match (bought_items.peek(), combined_items.peek()) {
+                (Some(bought), Some(combined)) if combined.0 < bought.0 =>
+                    combined_items.next(),
+                (Some(_), _) => bought_items.next(),
+                (None, _) => combined_items.next(),
+            }
+        });
+
+        items_of_tiers.push(
+            owned_items
+                .chain(merged)
+                .take(get_total_required_amount(
+                    recipe.ratio,
+                    tier,
+                    target_tier,
+                ))
+                .collect(),
+        );
+    }
+
+    items_of_tiers
+}
+
+#[inline]
+pub(crate) async fn tiered_upgrade_planner(
+    start_without_user_input: &mut Option<Instant>,
+) -> bool {
+    let presets = presets();
+
+    println!("Select the item to upgrade:");
+
+    for (number, preset) in (1..).zip(&presets) {
+        println!(" {}. {}", number.to_string().bright_blue(), preset.name);
+    }
+
+    let custom_selection = presets.len() + 1;
+
+    println!(" {}. Custom recipe", custom_selection.to_string().bright_blue());
+
+    let selection =
+        ask_usize_input("Enter the number of the item: ", 1, custom_selection);
+
+    let recipe = presets
+        .into_iter()
+        .nth(selection - 1)
+        .unwrap_or_else(ask_custom_recipe);
+
+    let current_tier = ask_usize_input(
+        &format!(
+            "Enter your current {} tier, or 0 if you don't have one: ",
+            recipe.name
+        ),
+        0,
+        recipe.tiers() - 1,
+    );
+    let target_tier = ask_usize_input(
+        &format!("Enter your target {} tier: ", recipe.name),
+        current_tier + 1,
+        recipe.tiers(),
+    );
+
+    let owned = ask_owned_items(&recipe, current_tier, target_tier);
+
+    *start_without_user_input = Some(Instant::now());
+
+    print_upgrade_plan(&recipe, &owned, target_tier).await
+}
+
+// Asks how many items are owned of each tier below the target, returning the
+// amount of each tier with the current item included. The current tier is 0
+// if there is no current item.
+#[inline]
+#[must_use]
+pub(crate) fn ask_owned_items(
+    recipe: &CombineRecipe,
+    current_tier: usize,
+    target_tier: usize,
+) -> Vec<usize> {
+    let mut owned = vec![0; recipe.tiers()];
+
+    if let Some(current) =
+        current_tier.checked_sub(1).and_then(|index| owned.get_mut(index))
+    {
+        *current += 1;
+    }
+
+    println!(
+        "Enter how many other {}s you have of each tier, they will be combined too if it's cheaper:",
+        recipe.name
+    );
+
+    for (tier, owned) in (1..target_tier).zip(&mut owned) {
+        *owned += ask_usize_input(&format!(" Tier {tier}: "), 0, 10_000);
+    }
+
+    owned
+}
+
+#[inline]
+#[must_use]
+fn ask_custom_recipe() -> CombineRecipe {
+    let name = ask_string_input("Enter the name of the item: ");
+
+    let id = loop {
+        let id = ask_string_input(&format!(
+            "Enter the item ID with {TIER_PLACEHOLDER} in place of the tier number, i.e. MASTER_SKULL_TIER_{TIER_PLACEHOLDER}: "
+        ));
+
+        if id.contains(TIER_PLACEHOLDER) {
+            break id;
+        }
+
+        eprintln!(
+            "{}",
+            format!("The item ID must contain {TIER_PLACEHOLDER}!")
+                .bright_red()
+        );
+    };
+
+    let tiers = ask_usize_input("Enter the number of tiers: ", 2, 20);
+    let ratio = ask_usize_input(
+        "Enter how many items of a tier combine into the next tier: ",
+        2,
+        64,
+    );
+    let combine_fee = i64::from(ask_int_input(
+        "Enter the coins paid for each combine: ",
+        Some(0),
+        None,
+    ));
+
+    CombineRecipe::with_tiered_id(&name, &id, tiers, ratio, combine_fee)
+}
+
+#[inline]
+#[must_use]
+fn ask_usize_input(question: &str, min: usize, max: usize) -> usize {
+    if let (Ok(min), Ok(max)) = (i32::try_from(min), i32::try_from(max)) {
+        usize::try_from(ask_int_input(question, Some(min), Some(max)))
+            .unwrap_or(0)
+    } else {
+        eprintln!("{}", "error: input range is too large".red());
+
+        min
+    }
+}
+
+// Fetches the listings of every tier and prints the cheapest plan, returning
+// false if the listings can't be fetched.
+#[inline]
+pub(crate) async fn print_upgrade_plan(
+    recipe: &CombineRecipe,
+    owned: &[usize],
+    target_tier: usize,
+) -> bool {
+    // Deep enough to buy everything from the lowest tier, if that's not too
+    // many.
+    let depth = get_total_required_amount(recipe.ratio, 1, target_tier)
+        .min(MAXIMUM_LISTING_DEPTH);
+
+    let Some(listings) =
+        fetch_bin_listings(&recipe.ids, recipe.category.as_deref(), depth)
+            .await
+    else {
+        eprintln!(
+            "{}",
+            "Critical error(s) occurred while running the program. Please read above for details.".red()
+        );
+
+        return false;
+    };
+
+    // The plan buys the listings as they are, the median lowest BIN is shown
+    // next to the cheapest listing and used for the resell price if the
+    // prices are smoothed.
+    let smoothing_window = price_smoothing_window();
+    let lowest_prices = smooth_lowest_bin_prices(
+        &recipe.ids,
+        listings
+            .iter()
+            .map(|listings| listings.prices.first().copied())
+            .collect(),
+    );
+
+    println!();
+
+    for (tier, (listings, &lowest_price)) in
+        (1..).zip(listings.iter().zip(&lowest_prices))
+    {
+        let Some(&price) = listings.prices.first() else {
+            println!(
+                "{}",
+                format!("No one is selling {} - Tier {tier}!", recipe.name)
+                    .bright_red()
+            );
+
+            continue;
+        };
+
+        let total = listings.total.map_or_else(
+            || {
+                format!(
+                    "at least {} listings, the price provider doesn't know the total",
+                    listings.prices.len().to_string().yellow()
+                )
+            },
+            |total| format!("{} listings", total.to_string().yellow()),
+        );
+
+        println!(
+            "{} - Tier {tier} is priced {} at the lowest, with {total}",
+            recipe.name,
+            format_coins(price).yellow()
+        );
+
+        if let Some(window_seconds) = smoothing_window
+            && let Some(median) = lowest_price
+        {
+            println!(
+                " The median lowest BIN over the last {} is {}",
+                format_age(window_seconds),
+                format_coins(median).yellow()
+            );
+        }
+    }
+
+    println!();
+
+    let Some(plan) =
+        plan_cheapest_upgrade(recipe, &listings, owned, target_tier)
+    else {
+        println!(
+            "{}",
+            format!(
+                "There are not enough {}s for sale to upgrade to Tier {target_tier}.",
+                recipe.name
+            )
+            .bright_red()
+        );
+
+        return true;
+    };
+
+    print_combine_plan(recipe, &listings, &plan, target_tier);
+
+    if let Some(&Some(price)) = lowest_prices.get(target_tier - 1) {
+        let price = i64_to_f64(price);
+
+        println!();
+        println!("Reselling the {} - Tier {target_tier} later:", recipe.name);
+        print_resell_price(
+            &smoothing_window.map_or_else(
+                || "BIN auction at the lowest BIN".to_owned(),
+                |window_seconds| {
+                    format!(
+                        "BIN auction at the median lowest BIN over the last {}",
+                        format_age(window_seconds)
+                    )
+                },
+            ),
+            price,
+            fee_rates().bin_sale_fees(price),
+        );
+    }
+
+    true
+}
+
+#[inline]
+fn print_combine_plan(
+    recipe: &CombineRecipe,
+    listings: &[BinListings],
+    plan: &CombinePlan,
+    target_tier: usize,
+) {
+    if plan.combines.is_empty()
+        && plan.bought.iter().all(|&bought| bought == 0)
+    {
+        println!(
+            "{}",
+            format!(
+                "You already have a {} - Tier {target_tier} or higher, exiting.",
+                recipe.name
+            )
+            .bright_green()
+        );
+
+        return;
+    }
+
+    println!(
+        "The cheapest way to get {} - Tier {target_tier} is to:",
+        recipe.name
+    );
+
+    for (tier, &used) in (1..).zip(&plan.used) {
+        if used > 0 {
+            println!(" - Use {used}x of your {} - Tier {tier}", recipe.name);
+        }
+    }
+
+    for (tier, (listings, &amount)) in
+        (1..).zip(listings.iter().zip(&plan.bought))
+    {
+        if let Some((cost, marginal_price)) = listings.cost_to_buy(amount)
+            && amount > 0
+        {
+            println!(
+                " - Buy {amount}x of {} - Tier {tier} for {} coins, with the most expensive one costing {} coins",
+                recipe.name,
+                format_coins(cost).yellow(),
+                format_coins(marginal_price).yellow()
+            );
+        }
+    }
+
+    if !plan.combines.is_empty() {
+        println!("Then combine them in this order:");
+
+        for (step, combines) in
+            (1..).zip(plan.combines.chunk_by(|tier, next| tier == next))
+        {
+            let tier = combines[0];
+
+            println!(
+                " {step}. Combine {}x of {} - Tier {} into {}x of {} - Tier {tier}",
+                combines.len() * recipe.ratio,
+                recipe.name,
+                tier - 1,
+                combines.len(),
+                recipe.name
+            );
+        }
+    }
+
+    if plan.combine_fees > 0 {
+        println!(
+            "Which would cost you {} coins in total, including {} coins of combine fees.",
+            format_coins(plan.total_cost).yellow(),
+            format_coins(plan.combine_fees).yellow()
+        );
+    } else {
+        println!(
+            "Which would cost you {} coins in total.",
+            format_coins(plan.total_cost).yellow()
+        );
+    }
+}
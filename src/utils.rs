@@ -1,3 +1,5 @@
+use core::cmp;
+
 use std::env;
 use std::fs;
 use std::fs::File;
@@ -8,6 +10,7 @@
 use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
+use std::process;
 use std::thread::available_parallelism;
 use std::time::SystemTime;
 
@@ -85,6 +88,21 @@ pub(crate) fn f64_to_i32(f64: f64) -> i32 {
     })
 }
 
+#[inline]
+#[must_use]
+pub(crate) fn f64_to_i64(f64: f64) -> i64 {
+    i64::from_f64(f64).unwrap_or_else(|| {
+        eprintln!("{}{f64}", "warning: loss of precision while converting from f64 to i64, if this is intentional, call .trunc() on the value before calling this function. f64 value: ".yellow());
+
+        // i64::from is not implemented for f64 so using as is the only option.
+        #[expect(clippy::cast_possible_truncation)]
+        #[expect(clippy::as_conversions)]
+        {
+            f64 as i64
+        }
+    })
+}
+
 #[inline]
 #[must_use]
 pub(crate) fn u128_to_u64(u128: u128) -> u64 {
@@ -148,6 +166,25 @@ pub(crate) fn u32_to_i32(u32: u32) -> i32 {
         })
 }
 
+#[inline]
+#[must_use]
+pub(crate) fn usize_to_i32(usize: usize) -> i32 {
+    i32::from_usize(usize).unwrap_or_else(|| {
+        eprintln!(
+            "{}{usize}",
+            "warning: loss of precision while converting usize to i32: "
+                .yellow()
+        );
+
+        #[expect(clippy::cast_possible_truncation)]
+        #[expect(clippy::cast_possible_wrap)]
+        #[expect(clippy::as_conversions)]
+        {
+            usize as i32
+        }
+    })
+}
+
 // Result<T, E> like enum but without the result and error.
 // This useful if a function can fail without an error.
 pub(crate) enum FunctionResult {
@@ -376,6 +413,20 @@ pub(crate) fn with_comma_separators(s: &str) -> Option<String> {
     Some(out)
 }
 
+#[inline]
+#[must_use]
+pub(crate) fn format_coins(coins: i64) -> String {
+    with_comma_separators(&coins.to_string())
+        .unwrap_or_else(|| coins.to_string())
+}
+
+// Rounded to whole coins.
+#[inline]
+#[must_use]
+pub(crate) fn format_coins_f64(coins: f64) -> String {
+    format_coins(f64_to_i64(coins.round()))
+}
+
 #[inline]
 pub(crate) fn print(text: &str) {
     print!("{text}");
@@ -384,6 +435,51 @@ pub(crate) fn print(text: &str) {
     }
 }
 
+// Returns how well the query matches the text, higher is better, or None if
+// it doesn't match at all. Every whitespace separated word in the query must
+// appear in the text either as a whole or with its letters in order. Case is
+// ignored.
+#[inline]
+#[must_use]
+pub(crate) fn fuzzy_match_score(query: &str, text: &str) -> Option<i32> {
+    let text = text.to_lowercase();
+    let mut score = 0;
+
+    for word in query.to_lowercase().split_whitespace() {
+        if let Some(position) = text.find(word) {
+            // Whole word matches are better than anything else, even more so
+            // if they are at the start of a word in the text.
+            score += 100;
+
+            if position == 0
+                || text[..position].ends_with(|c: char| !c.is_alphanumeric())
+            {
+                score += 50;
+            }
+        } else {
+            let mut remaining_text = text.chars();
+            let mut skipped_chars = 0;
+
+            for c in word.chars() {
+                loop {
+                    let next = remaining_text.next()?;
+
+                    if next == c {
+                        break;
+                    }
+
+                    skipped_chars += 1;
+                }
+            }
+
+            // Letters being closer together means a better match.
+            score += cmp::max(1, 50 - skipped_chars);
+        }
+    }
+
+    Some(score)
+}
+
 #[inline]
 #[must_use]
 pub(crate) fn ask_int_input(
@@ -448,6 +544,43 @@ pub(crate) fn write_file(file: &Path, text: &str) -> bool {
     }
 }
 
+// Writes to a temporary file next to the file and renames it over the file,
+// so other processes reading the file never see it half written.
+#[inline]
+pub(crate) fn write_file_atomically(file: &Path, text: &str) -> bool {
+    let mut temporary_file = file.as_os_str().to_owned();
+
+    temporary_file.push(format!(".{}.tmp", process::id()));
+
+    let temporary_file = PathBuf::from(temporary_file);
+
+    if !write_file(&temporary_file, text) {
+        return false;
+    }
+
+    match fs::rename(&temporary_file, file) {
+        Ok(()) => true,
+
+        Err(e) => {
+            eprintln!(
+                "{}{}: {e}",
+                "error: can't write to file: ".red(),
+                file.to_string_lossy()
+            );
+
+            if let Err(e) = fs::remove_file(&temporary_file) {
+                eprintln!(
+                    "{}{}: {e}",
+                    "warning: can't remove temporary file: ".yellow(),
+                    temporary_file.to_string_lossy()
+                );
+            }
+
+            false
+        },
+    }
+}
+
 #[inline]
 #[must_use]
 pub(crate) fn lines_from_file_from_end(
@@ -547,6 +680,33 @@ pub(crate) fn is_same_file(
     Ok(true)
 }
 
+#[inline]
+#[must_use]
+pub(crate) fn ask_string_input(question: &str) -> String {
+    loop {
+        print(question);
+
+        let next_line = io::stdin().lock().lines().next();
+
+        if let Some(result) = next_line {
+            match result {
+                Ok(line) => return line.trim().to_owned(),
+
+                Err(e) => {
+                    eprintln!(
+                        "{}{e}",
+                        "Error when getting line input: ".bright_red()
+                    );
+                },
+            }
+        } else {
+            eprintln!("{}", "error: no more lines".bright_red());
+        }
+
+        println!();
+    }
+}
+
 #[inline]
 #[must_use]
 pub(crate) fn ask_float_input(